@@ -1,11 +1,33 @@
-use std::{collections::HashMap, fmt::Debug, path::Path};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    path::{Path, PathBuf},
+};
 
 use serde::{de::DeserializeOwned, Serialize};
 
 mod config_error_impl;
 mod config_impl;
+#[cfg(feature = "consul")]
+mod consul_impl;
+#[cfg(feature = "encryption")]
+mod encryption_impl;
+#[cfg(feature = "etcd")]
+mod etcd_impl;
+#[cfg(feature = "http")]
+mod http_impl;
+#[cfg(feature = "schema")]
+mod schema_impl;
+#[cfg(feature = "sops")]
+mod sops_impl;
+#[cfg(feature = "sqlite")]
+mod sqlite_impl;
+#[cfg(feature = "validator")]
+mod validate_impl;
 mod value_impl;
 mod value_serde_impl;
+#[cfg(feature = "vault")]
+mod vault_impl;
 
 /// Valid values for configs
 #[derive(Debug, Clone, PartialEq, Serialize)]
@@ -26,9 +48,504 @@ pub struct ConfigError {
 }
 impl ConfigError {
     pub(crate) const CONFIG_ERROR_PREFIX: &'static str = "[CONFIG][ERROR]";
+
+    /// Dotted key path of the value causing the error, if any, e.g. the encrypted value failed to decrypt, or the first failed validation rule.
+    pub fn key_path(&self) -> Option<&str> {
+        self.config_error_impl.key_path()
+    }
+
+    /// Failed validation rules of `Config::build_validated()`, empty for other errors.
+    pub fn validation_errors(&self) -> &[ValidationError] {
+        self.config_error_impl.validation_errors()
+    }
+
+    /// Values of the merged config failing its JSON Schema, empty for other errors, requires `schema` feature.
+    #[cfg(feature = "schema")]
+    pub fn schema_violations(&self) -> &[SchemaViolation] {
+        self.config_error_impl.schema_violations()
+    }
+}
+
+/// JSON Schema of T, from its `#[derive(schemars::JsonSchema)]`, requires `schema` feature.
+///
+/// Write it to a file for editors to autocomplete config files, e.g. with `# yaml-language-server: $schema=config.schema.json`
+/// in yaml files, or `#:schema config.schema.json` in toml files.
+///
+/// Example:
+/// ```rust,ignore
+/// let schema = configrs::config::json_schema::<Cfg>();
+/// std::fs::write("config.schema.json", serde_json::to_string_pretty(&schema)?)?;
+/// ```
+#[cfg(feature = "schema")]
+pub fn json_schema<T: schemars::JsonSchema + ?Sized>() -> serde_json::Value {
+    schema_impl::generate::<T>()
+}
+
+/// Generate a random key for encrypted values, 32 bytes encoded in base64, requires `encryption` feature.
+#[cfg(feature = "encryption")]
+pub fn generate_key() -> String {
+    encryption_impl::generate_key()
+}
+
+/// Encrypt a string, number or bool value with key, into `ENC[AES256_GCM,data:..,iv:..,tag:..,type:..]`, requires `encryption` feature.
+///
+/// Put the encrypted value in config files, it's decrypted into a value of the same type on build, see `Config::with_decryption_key_file(..)`.
+///
+/// Example:
+/// ```rust,ignore
+/// use configrs::config::{encrypt_value, generate_key};
+///
+/// let key = generate_key();
+/// let password = encrypt_value(&key, "hunter2")?;
+/// ```
+#[cfg(feature = "encryption")]
+pub fn encrypt_value<V>(key: &str, value: V) -> Result<String, ConfigError>
+where
+    V: Into<Value>,
+{
+    let key = encryption_impl::parse_key(key)
+        .map_err(config_error_impl::ConfigErrorImpl::EncryptionError)?;
+    let value = serde_json::Value::from(value.into());
+    Ok(encryption_impl::encrypt(&key, &value, &[])
+        .map_err(config_error_impl::ConfigErrorImpl::EncryptionError)?)
+}
+
+/// Precedence layer of a source.
+///
+/// Sources are merged layer by layer, from lowest to highest, regardless of declaration order: `Defaults` < `File` < `Env` < `Override`.
+/// A source in a higher layer overwrites the key paths it sets in lower layers: top level keys for env vars, .env, values and files,
+/// nested key paths for command-line arguments.
+///
+/// Default layer of each source:
+/// - `Env`: env vars(`Config::new()`), .env file, values,
+/// - `File`: json, json5, toml, yaml and ron files, url, Consul, etcd, Vault, SQLite and SOPS sources,
+/// - `Override`: command-line arguments, removed keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layer {
+    Defaults,
+    File,
+    Env,
+    Override,
+}
+
+/// Policy when a source sets a key already set by a previously declared source in the same layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Conflict {
+    /// Fail build with duplicate key error.
+    #[default]
+    Error,
+    /// Keep value of previously declared source.
+    FirstWins,
+    /// Overwrite with value of later declared source.
+    LastWins,
+}
+
+/// Strategy when an array is set at a key path already holding an array, by a higher layer or by another source of the same layer.
+///
+/// Arrays meeting in the same layer are combined by any strategy other than `Replace`, instead of applying the conflict policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArrayMerge {
+    /// Replace the previous array.
+    #[default]
+    Replace,
+    /// Add elements after the previous ones.
+    Append,
+    /// Add elements before the previous ones.
+    Prepend,
+    /// Add elements not already in the previous array.
+    Union,
+    /// Merge elements at the same index, tables are merged key by key and other values are replaced.
+    ByIndex,
+    /// Merge tables having the same value of the id field, e.g. `ById("name")`, and add the others.
+    ById(&'static str),
+}
+
+/// Format of a document read from remote sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Json5,
+    Toml,
+    Yaml,
+    Ron,
+}
+
+/// Kind of a declared source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SourceKind {
+    /// Environment variables of the process, `Config::new()`.
+    OsEnv,
+    /// Injected environment variables, `.with_env_vars(..)`.
+    EnvVars,
+    /// Value set programmatically, with its key.
+    Value(String),
+    EnvFile(PathBuf),
+    Json(PathBuf),
+    Json5(PathBuf),
+    Toml(PathBuf),
+    Yaml(PathBuf),
+    Ron(PathBuf),
+    /// Document fetched from HTTP/HTTPS url, with its format.
+    #[cfg(feature = "http")]
+    Url(String, Format),
+    /// Consul KV key prefix, with agent address.
+    #[cfg(feature = "consul")]
+    Consul(String, String),
+    /// etcd key prefix, with gateway address.
+    #[cfg(feature = "etcd")]
+    Etcd(String, String),
+    /// Vault KV v2 secret url, with the key path it's merged under, if any.
+    #[cfg(feature = "vault")]
+    Vault(String, Option<String>),
+    /// SQLite database file, with the query of its rows.
+    #[cfg(feature = "sqlite")]
+    Sqlite(PathBuf, String),
+    /// SOPS encrypted file, with its format, json or yaml.
+    #[cfg(feature = "sops")]
+    Sops(PathBuf, Format),
+    Args,
+    #[cfg(feature = "clap")]
+    Clap,
+    /// Async source, with its name.
+    #[cfg(feature = "async")]
+    Async(String),
+    /// Removed key, `.without_key(..)`.
+    Unset(String),
+}
+
+impl std::fmt::Display for SourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SourceKind::OsEnv => write!(f, "env vars"),
+            SourceKind::EnvVars => write!(f, "injected env vars"),
+            SourceKind::Value(key) => write!(f, "value {}", key),
+            SourceKind::EnvFile(file_path) => write!(f, ".env file {}", file_path.display()),
+            SourceKind::Json(file_path) => write!(f, "json file {}", file_path.display()),
+            SourceKind::Json5(file_path) => write!(f, "json5 file {}", file_path.display()),
+            SourceKind::Toml(file_path) => write!(f, "toml file {}", file_path.display()),
+            SourceKind::Yaml(file_path) => write!(f, "yaml file {}", file_path.display()),
+            SourceKind::Ron(file_path) => write!(f, "ron file {}", file_path.display()),
+            #[cfg(feature = "http")]
+            SourceKind::Url(url, _) => write!(f, "url {}", url),
+            #[cfg(feature = "consul")]
+            SourceKind::Consul(addr, prefix) => write!(f, "Consul prefix {} at {}", prefix, addr),
+            #[cfg(feature = "etcd")]
+            SourceKind::Etcd(addr, prefix) => write!(f, "etcd prefix {} at {}", prefix, addr),
+            #[cfg(feature = "vault")]
+            SourceKind::Vault(url, _) => write!(f, "Vault secret {}", url),
+            #[cfg(feature = "sqlite")]
+            SourceKind::Sqlite(file_path, _) => {
+                write!(f, "SQLite database {}", file_path.display())
+            }
+            #[cfg(feature = "sops")]
+            SourceKind::Sops(file_path, _) => write!(f, "SOPS file {}", file_path.display()),
+            SourceKind::Args => write!(f, "command-line arguments"),
+            #[cfg(feature = "clap")]
+            SourceKind::Clap => write!(f, "clap arguments"),
+            #[cfg(feature = "async")]
+            SourceKind::Async(name) => write!(f, "async source {}", name),
+            SourceKind::Unset(key) => write!(f, "removed key {}", key),
+        }
+    }
+}
+
+/// Description of a declared source, with its layer and conflict policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceInfo {
+    pub kind: SourceKind,
+    pub layer: Layer,
+    pub conflict: Conflict,
+}
+
+/// Secret value, deserialized like T but redacted when debug printed, e.g. in logs of the whole config.
+///
+/// Example:
+/// ```rust
+/// use configrs::config::Secret;
+///
+/// let password: Secret<String> = serde_json::from_str("\"hunter2\"").unwrap();
+/// assert_eq!(format!("{:?}", password), "Secret([REDACTED])");
+/// assert_eq!(password.expose(), "hunter2");
+/// ```
+#[derive(Clone, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(transparent)]
+pub struct Secret<T>(T);
+
+impl<T> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// The secret value.
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Debug for Secret<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+// same schema as T, like its deserialization
+#[cfg(feature = "schema")]
+impl<T: schemars::JsonSchema> schemars::JsonSchema for Secret<T> {
+    fn inline_schema() -> bool {
+        T::inline_schema()
+    }
+
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        T::schema_name()
+    }
+
+    fn schema_id() -> std::borrow::Cow<'static, str> {
+        T::schema_id()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        T::json_schema(generator)
+    }
+}
+
+/// Failed validation rule, with the dotted key path of the value, e.g. `database.port`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Dotted key path, empty for a failure of the config itself.
+    pub key_path: String,
+    /// Message of the failed rule.
+    pub message: String,
+}
+
+/// Semantic rules of a config type, checked by `Config::build_validated()` after deserialization.
+///
+/// Example:
+/// ```rust
+/// use configrs::config::{Validate, Validator};
+///
+/// struct Database {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// struct Cfg {
+///     database: Database,
+///     url: Option<String>,
+///     socket: Option<String>,
+/// }
+///
+/// impl Validate for Database {
+///     fn validate(&self, v: &mut Validator) {
+///         v.check("host", !self.host.is_empty(), "must not be empty")
+///             .check("port", self.port >= 1024, "must be at least 1024");
+///     }
+/// }
+///
+/// impl Validate for Cfg {
+///     fn validate(&self, v: &mut Validator) {
+///         v.nested("database", &self.database)
+///             .check("url", self.url.is_some() || self.socket.is_some(), "either url or socket must be set");
+///     }
+/// }
+///
+/// let cfg = Cfg { database: Database { host: String::new(), port: 80 }, url: None, socket: None };
+/// let mut v = Validator::new();
+/// cfg.validate(&mut v);
+/// assert_eq!(v.errors().len(), 3);
+/// assert_eq!(v.errors()[0].key_path, "database.host");
+/// ```
+pub trait Validate {
+    fn validate(&self, v: &mut Validator);
+}
+
+impl<T: Validate> Validate for Option<T> {
+    fn validate(&self, v: &mut Validator) {
+        if let Some(value) = self {
+            value.validate(v);
+        }
+    }
+}
+
+impl<T: Validate> Validate for Vec<T> {
+    fn validate(&self, v: &mut Validator) {
+        for (i, value) in self.iter().enumerate() {
+            v.nested(&i.to_string(), value);
+        }
+    }
+}
+
+/// Collector of validation failures by key path, relative to the value being validated.
+#[derive(Debug, Default)]
+pub struct Validator {
+    path: Vec<String>,
+    errors: Vec<ValidationError>,
+}
+
+impl Validator {
+    /// Create validator for the root of the config, without failures.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a failure at dotted key path, relative to the current value, unless ok.
+    pub fn check(&mut self, key: &str, ok: bool, message: impl Into<String>) -> &mut Self {
+        if !ok {
+            self.error(key, message);
+        }
+        self
+    }
+
+    /// Add a failure at dotted key path, relative to the current value, an empty key for the current value itself.
+    pub fn error(&mut self, key: &str, message: impl Into<String>) -> &mut Self {
+        let key_path = self
+            .path
+            .iter()
+            .map(String::as_str)
+            .chain(key.split('.').filter(|k| !k.is_empty()))
+            .collect::<Vec<_>>()
+            .join(".");
+        self.errors.push(ValidationError {
+            key_path,
+            message: message.into(),
+        });
+        self
+    }
+
+    /// Validate nested value at dotted key path, its failures are prefixed with the key path.
+    pub fn nested<V: Validate + ?Sized>(&mut self, key: &str, value: &V) -> &mut Self {
+        let len = self.path.len();
+        self.path
+            .extend(key.split('.').filter(|k| !k.is_empty()).map(String::from));
+        value.validate(self);
+        self.path.truncate(len);
+        self
+    }
+
+    /// Add failures of rules generated by `#[derive(validator::Validate)]`, requires `validator` feature.
+    ///
+    /// Failures are reported by Rust field names, not by keys renamed with `#[serde(rename)]`.
+    #[cfg(feature = "validator")]
+    pub fn derived<V: validator::Validate + ?Sized>(&mut self, value: &V) -> &mut Self {
+        if let Err(errors) = value.validate() {
+            validate_impl::add_errors(self, &errors);
+        }
+        self
+    }
+
+    /// Failures added so far, in order.
+    pub fn errors(&self) -> &[ValidationError] {
+        &self.errors
+    }
+
+    /// Consume validator into its failures.
+    pub fn into_errors(self) -> Vec<ValidationError> {
+        self.errors
+    }
+}
+
+/// Value of the merged config failing its JSON Schema, requires `schema` feature.
+#[cfg(feature = "schema")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// JSON pointer of the value, e.g. `/database/port`, empty for the whole config.
+    pub pointer: String,
+    pub message: String,
+    /// Source supplying the value, none if no source set it, e.g. a missing required key.
+    pub source: Option<SourceKind>,
+}
+
+/// Lease and version of a Vault secret, requires `vault` feature.
+#[cfg(feature = "vault")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultMetadata {
+    /// Url of the secret.
+    pub url: String,
+    /// Version of the secret, increased on each write.
+    pub version: u64,
+    pub created_time: String,
+    pub lease_id: String,
+    /// Time before the secret should be read again, zero if it's not leased.
+    pub lease_duration: std::time::Duration,
+    pub renewable: bool,
+}
+
+/// Watcher of changes of a SQLite database, to reload configs of `.with_sqlite(..)` sources, requires `sqlite` feature.
+///
+/// It keeps a read-only connection and compares `PRAGMA data_version`, changed by transactions committed by other connections.
+///
+/// Example:
+/// ```rust,ignore
+/// let config = Config::new().with_sqlite("settings.db", "settings");
+/// let mut watcher = SqliteWatcher::new("settings.db")?;
+/// loop {
+///     std::thread::sleep(Duration::from_secs(5));
+///     if watcher.changed()? {
+///         let cfg = config.build::<Cfg>()?;
+///         // apply cfg
+///     }
+/// }
+/// ```
+#[cfg(feature = "sqlite")]
+pub struct SqliteWatcher {
+    watcher: sqlite_impl::Watcher,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteWatcher {
+    pub fn new(file_path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        Ok(Self {
+            watcher: sqlite_impl::Watcher::new(file_path.as_ref())?,
+        })
+    }
+
+    /// Whether the database has changed since the watcher was created or last checked.
+    pub fn changed(&mut self) -> Result<bool, ConfigError> {
+        Ok(self.watcher.changed()?)
+    }
+}
+
+/// Boxed future returned by async sources.
+#[cfg(feature = "async")]
+pub type BoxFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
+
+/// Source loaded asynchronously, e.g. from network stores, requires `async` feature.
+///
+/// Example:
+/// ```rust,ignore
+/// use configrs::config::{AsyncSource, BoxFuture, Value};
+///
+/// struct Remote;
+///
+/// impl AsyncSource for Remote {
+///     fn load(&self) -> BoxFuture<'_, Result<Value, Box<dyn std::error::Error + Send + Sync>>> {
+///         Box::pin(async move {
+///             let body = fetch("https://config.example.com/app.json").await?;
+///             Ok(Value::from(serde_json::from_str::<serde_json::Value>(&body)?))
+///         })
+///     }
+/// }
+/// ```
+#[cfg(feature = "async")]
+pub trait AsyncSource: Send + Sync {
+    /// Name of the source, shown by `Config::sources()`.
+    fn name(&self) -> String {
+        String::from("async")
+    }
+
+    /// Load values of the source, `Value::Map` of top level keys like a file.
+    fn load(&self) -> BoxFuture<'_, Result<Value, Box<dyn std::error::Error + Send + Sync>>>;
 }
 
 /// Configuration builder to build your configs.
+///
+/// Sources are only declared by the builder, and read on each build, e.g. env vars are snapshot and files are read and parsed in `.build()`.
+/// So a builder is cheap to clone, can be defined in a `static` with `std::sync::LazyLock`, and reloaded by building again.
+/// Use `.load()` to read sources once and build many types from the same snapshot.
 #[derive(Clone)]
 pub struct Config {
     config_impl: config_impl::ConfigImpl,
@@ -37,12 +554,49 @@ pub struct Config {
 impl Config {
     /// Initialized configs from environment variables.
     ///
+    /// Environment variables are snapshot on each build.
     pub fn new() -> Self {
         Self {
             config_impl: config_impl::ConfigImpl::new(),
         }
     }
 
+    /// Initialized configs without any source, not even environment variables.
+    ///
+    /// Add OS environment with `.with_env_vars(std::env::vars())`, or inject fake environment variables for isolated tests.
+    pub fn empty() -> Self {
+        Self {
+            config_impl: config_impl::ConfigImpl::empty(),
+        }
+    }
+
+    /// Add environment variables from any key and value pairs, e.g. `std::env::vars()` or a fake environment in tests
+    ///
+    /// They're handled like env vars of `Config::new()`: filtered with prefix, parsed into arrays and maps, in `Layer::Env`.
+    ///
+    /// Example:
+    /// ```rust
+    /// use configrs::config::Config;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Cfg {
+    ///     port: u16,
+    /// }
+    /// let cfg = Config::empty().with_env_vars([("port", "8080")]).build::<Cfg>();
+    /// assert_eq!(cfg.unwrap().port, 8080);
+    /// ```
+    pub fn with_env_vars<I, K, V>(self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        Self {
+            config_impl: self.config_impl.with_env_vars(vars),
+        }
+    }
+
     /// Set key and value programmatically.
     ///
     /// Accepted `Value`:
@@ -62,19 +616,99 @@ impl Config {
         }
     }
 
+    /// Normalize keys of every source into snake_case while merging, e.g. `DATABASE_URL`, `database-url` and `databaseUrl` into `database_url`
+    ///
+    /// Keys are case folded, and words of kebab-case, snake_case and camelCase keys are joined with `_`, so they fill snake_case fields without serde aliases.
+    /// Keys colliding after normalization fail build with duplicate key error naming both original spellings.
+    ///
+    /// Prefix filtering applies to original env vars names, then the prefix is stripped before normalizing,
+    /// e.g. `APP_DATABASE_URL` fills `database_url` with `.with_env_prefix("APP_")`. Array merge strategies key paths are normalized as well.
+    /// As env vars like `http_proxy` and `HTTP_PROXY` collide, use it with `.with_env_prefix(..)` to keep unrelated env vars out.
+    ///
+    /// Keys at every depth are normalized, including keys of map fields like `HashMap<String, String>` labels or headers,
+    /// e.g. `X-Request-Id` into `x_request_id`. Use `.with_raw_keys_at(..)` to keep the keys of such tables as they are.
+    pub fn with_normalized_keys(self) -> Self {
+        Self {
+            config_impl: self.config_impl.with_normalized_keys(),
+        }
+    }
+
+    /// Keep keys inside the table at dotted key path as they are with `.with_normalized_keys()`, e.g. `.with_raw_keys_at("server.headers")`
+    ///
+    /// The key path itself is matched after normalization, keys below it are neither normalized nor checked for collisions.
+    pub fn with_raw_keys_at(self, key: &str) -> Self {
+        Self {
+            config_impl: self.config_impl.with_raw_keys_at(key),
+        }
+    }
+
     /// Filter configs from environment variables and .env file with prefix
     ///
     ///
     /// It doesn't filter configs from json, toml and yaml.
+    ///
+    /// The prefix is kept in the keys, unless `.with_normalized_keys()` strips it, e.g. `APP_PORT` fills `port`.
     pub fn with_env_prefix(self, prefix: &'static str) -> Self {
         Self {
             config_impl: self.config_impl.with_env_prefix(prefix),
         }
     }
 
+    /// Parse env var into array of strings, splitting its value by separator, e.g. `HOSTS=a,b,c` into `["a", "b", "c"]`
+    ///
+    /// Applies to env vars and .env file. Separators inside `"` or `'` quotes are kept, e.g. `HOSTS="a,b",c` into `["a,b", "c"]`.
+    /// Whitespaces outside quotes are trimmed, and an empty item after a trailing separator is dropped, e.g. `HOSTS=a, b,` into `["a", "b"]`.
+    ///
+    /// Example:
+    /// ```rust
+    /// use configrs::config::Config;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Cfg {}
+    /// let cfg = Config::new().with_env_list("HOSTS").with_env_separator(';').build::<Cfg>();
+    /// ```
+    pub fn with_env_list(self, key: &str) -> Self {
+        Self {
+            config_impl: self.config_impl.with_env_list(key),
+        }
+    }
+
+    /// Parse env var into map of strings, splitting its value by separator and items by `=`, e.g. `LABELS=team=core,tier=1` into `{ "team": "core", "tier": "1" }`
+    ///
+    /// Applies to env vars and .env file, with the same quoting and trimming as `.with_env_list(..)` for both keys and values,
+    /// e.g. `LABELS=team = core, motd=' hi '` into `{ "team": "core", "motd": " hi " }`.
+    pub fn with_env_map(self, key: &str) -> Self {
+        Self {
+            config_impl: self.config_impl.with_env_map(key),
+        }
+    }
+
+    /// Collect indexed env vars into array, e.g. `HOSTS_0=a` and `HOSTS_1=b` into `HOSTS` as `["a", "b"]`
+    ///
+    /// Applies to env vars and .env file. Items are ordered by index and parsed like other env vars values.
+    /// Indexes start at 0 without gaps, e.g. `HOSTS_0` and `HOSTS_5` alone fail build with env parsing error.
+    pub fn with_env_indexed(self, key: &str) -> Self {
+        Self {
+            config_impl: self.config_impl.with_env_indexed(key),
+        }
+    }
+
+    /// Set separator of `.with_env_list(..)` and `.with_env_map(..)` items, `,` by default.
+    pub fn with_env_separator(self, separator: char) -> Self {
+        Self {
+            config_impl: self.config_impl.with_env_separator(separator),
+        }
+    }
+
     /// Overwrite previous already existing configs keys
     ///
-    /// Once called, it activated overwriting for next configs callings.
+    /// Once called, sources declared after it use `Conflict::LastWins`, overwriting keys of previously declared sources in the same layer.
+    ///
+    /// It doesn't change precedence between layers: env vars, .env files and values (`Layer::Env`) overwrite keys of json, toml, yaml
+    /// and other files (`Layer::File`) with or without overwrite, and a key set by both is not a duplicate.
+    /// Before layers, such a key was a duplicate without overwrite, and the file declared after `.with_overwrite()` won.
+    /// To keep files winning over env vars, move env vars to a lower layer, e.g. `Config::new().with_layer(Layer::Defaults)`.
     ///
     /// Example:
     /// ```rust
@@ -83,17 +717,70 @@ impl Config {
     ///
     /// #[derive(Debug, Deserialize)]
     /// struct Cfg {}
-    /// // `.with_env("env_path.env")` will not overwrite what's in env(.new()), but `.with_toml("toml.toml")` will, because `.with_overwrite()` declared before.
-    /// let cfg = Config::new().with_env("env_path.env").with_overwrite().with_toml("toml.toml").build::<Cfg>();
+    /// // `.with_toml("toml.toml")` will overwrite `.with_json("json.json")`, because `.with_overwrite()` declared before,
+    /// // and env vars of `.new()` and `.with_env("env_path.env")` overwrite both files, as `Layer::Env` is above `Layer::File`.
+    /// let cfg = Config::new().with_env("env_path.env").with_json("json.json").with_overwrite().with_toml("toml.toml").build::<Cfg>();
     /// ```
     ///
-    /// Anything declared after `.with_overwrite` will overwrite anything before it in order of declarations.
+    /// Anything declared after `.with_overwrite` will overwrite anything before it in the same layer, in order of declarations.
+    /// Use `.with_conflict(..)` to set the policy of a single source.
     pub fn with_overwrite(self) -> Self {
         Self {
             config_impl: self.config_impl.with_overwrite(),
         }
     }
 
+    /// Set precedence layer of the last declared source, e.g. `.with_json("defaults.json").with_layer(Layer::Defaults)`.
+    ///
+    /// For multi-document yaml, it's applied to every document of the file.
+    pub fn with_layer(self, layer: Layer) -> Self {
+        Self {
+            config_impl: self.config_impl.with_layer(layer),
+        }
+    }
+
+    /// Set conflict policy of the last declared source, for keys already set by previously declared sources in the same layer.
+    ///
+    /// Example:
+    /// ```rust
+    /// use configrs::config::{Config, Conflict};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Cfg {}
+    /// let cfg = Config::new().with_json("base.json").with_json("local.json").with_conflict(Conflict::LastWins).build::<Cfg>();
+    /// ```
+    pub fn with_conflict(self, conflict: Conflict) -> Self {
+        Self {
+            config_impl: self.config_impl.with_conflict(conflict),
+        }
+    }
+
+    /// Set array merge strategy for every key path without its own strategy, `ArrayMerge::Replace` by default.
+    ///
+    /// Example:
+    /// ```rust
+    /// use configrs::config::{ArrayMerge, Config, Layer};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Cfg {}
+    /// // plugins of overlay.json are added after plugins of base.json
+    /// let cfg = Config::new().with_json("base.json").with_layer(Layer::Defaults).with_json("overlay.json").with_array_merge(ArrayMerge::Append).build::<Cfg>();
+    /// ```
+    pub fn with_array_merge(self, array_merge: ArrayMerge) -> Self {
+        Self {
+            config_impl: self.config_impl.with_array_merge(array_merge),
+        }
+    }
+
+    /// Set array merge strategy of the array at dotted key path, e.g. `.with_array_merge_at("server.allowed_origins", ArrayMerge::Union)`.
+    pub fn with_array_merge_at(self, key: &str, array_merge: ArrayMerge) -> Self {
+        Self {
+            config_impl: self.config_impl.with_array_merge_at(key, array_merge),
+        }
+    }
+
     /// Add configs from .env file
     ///
     pub fn with_env(self, file_path: impl AsRef<Path>) -> Self {
@@ -110,8 +797,21 @@ impl Config {
         }
     }
 
+    /// Add configs from .json5 file
+    ///
+    /// Also accepts commented JSON (.jsonc), e.g. tsconfig-style files: `//` and `/* */` comments, trailing commas, unquoted keys and single-quoted strings.
+    ///
+    /// Parsing errors are reported with line and column.
+    pub fn with_json5(self, file_path: impl AsRef<Path>) -> Self {
+        Self {
+            config_impl: self.config_impl.with_json5(file_path),
+        }
+    }
+
     /// Add configs from .toml file
     ///
+    /// Offset datetimes, local datetimes, local dates and local times are kept in their TOML text form, e.g. `1979-05-27T07:32:00-08:00`,
+    /// so they deserialize into `String` as well as into datetime types deserializing from strings, e.g. chrono or time.
     pub fn with_toml(self, file_path: impl AsRef<Path>) -> Self {
         Self {
             config_impl: self.config_impl.with_toml(file_path),
@@ -120,21 +820,552 @@ impl Config {
 
     /// Add configs from .yaml file
     ///
+    /// Multi-document files are supported, each `---` separated document is layered in order like separate sources,
+    /// later documents overwriting keys of earlier ones of the same file, e.g. Helm-style overrides, without `.with_overwrite()`.
+    ///
+    /// Anchors, aliases and `<<` merge keys are resolved into the merged map.
+    ///
+    /// Top level keys tagged `!unset`, e.g. `legacy: !unset`, remove the keys set by lower layers.
+    /// Nested keys tagged `!unset` are only left out of their table, which replaces the table of lower layers as a whole.
+    /// Keys set by previous sources of the same layer, other than earlier documents of the file, are duplicates unless `.with_conflict(Conflict::LastWins)`.
     pub fn with_yaml(self, file_path: impl AsRef<Path>) -> Self {
         Self {
             config_impl: self.config_impl.with_yaml(file_path),
         }
     }
 
+    /// Add configs from .ron file
+    ///
+    /// Enum variants are represented as single-key tables, e.g. `Custom(5)` as `{ "Custom": 5 }`, and unit variants as strings, so they deserialize into user enums.
+    ///
+    /// Named structs are treated the same way as enum variants, so omit the struct name, e.g. `(x: 1, y: 2)`, for plain struct fields.
+    pub fn with_ron(self, file_path: impl AsRef<Path>) -> Self {
+        Self {
+            config_impl: self.config_impl.with_ron(file_path),
+        }
+    }
+
+    /// Add configs from a document fetched from HTTP/HTTPS url, requires `http` feature.
+    ///
+    /// It's in `Layer::File` and merged like a file source of the format.
+    /// Use `.with_timeout(..)`, `.with_retries(..)`, `.with_header(..)` and `.with_cache(..)` right after it to configure the request,
+    /// they fail the build when the last declared source is not a url, Consul, etcd or Vault source.
+    ///
+    /// Example:
+    /// ```rust,ignore
+    /// use configrs::config::{Config, Format};
+    ///
+    /// let cfg = Config::new()
+    ///     .with_url("https://config.example.com/app.json", Format::Json)
+    ///     .with_header("Authorization", "Bearer token")
+    ///     .with_timeout(Duration::from_secs(5))
+    ///     .with_retries(3)
+    ///     .with_cache("/var/cache/app/config.json")
+    ///     .build::<Cfg>();
+    /// ```
+    #[cfg(feature = "http")]
+    pub fn with_url(self, url: &str, format: Format) -> Self {
+        Self {
+            config_impl: self.config_impl.with_url(url, format),
+        }
+    }
+
+    /// Add configs from Consul KV, every key under prefix read through the `/v1/kv` HTTP API of agent at addr, requires `consul` feature.
+    ///
+    /// Keys map to nested key paths relative to prefix, e.g. `app/db/port` under prefix `app` sets `port` inside `db`.
+    /// Values are decoded and parsed into bool, integer, float or string, like env vars.
+    ///
+    /// It's in `Layer::File`, and the modifiers of `.with_url(..)` apply to it, e.g. `.with_header("X-Consul-Token", token)` for ACL token.
+    /// Use `.watch_consul(..)` to wait for changes before building again.
+    #[cfg(feature = "consul")]
+    pub fn with_consul(self, addr: &str, prefix: &str) -> Self {
+        Self {
+            config_impl: self.config_impl.with_consul(addr, prefix),
+        }
+    }
+
+    /// Add configs from etcd, every key under prefix read by a range request to the v3 HTTP/JSON gateway at addr, requires `etcd` feature.
+    ///
+    /// Keys map to nested key paths relative to prefix, e.g. `/app/db/host` under prefix `/app` sets `host` inside `db`.
+    /// Values are parsed into bool, integer, float or string, like env vars.
+    ///
+    /// It's in `Layer::File`, and the modifiers of `.with_url(..)` apply to it, e.g. `.with_header("Authorization", token)` for auth token.
+    /// Use `.watch_etcd(..)` to wait for changes before building again.
+    #[cfg(feature = "etcd")]
+    pub fn with_etcd(self, addr: &str, prefix: &str) -> Self {
+        Self {
+            config_impl: self.config_impl.with_etcd(addr, prefix),
+        }
+    }
+
+    /// Add configs from fields of a Vault KV v2 secret, the latest version of secret at path of the engine mounted at mount, requires `vault` feature.
+    ///
+    /// Fields are merged as top level keys, keys with `.` included, e.g. `db.password` stays one key.
+    /// Use `Secret<T>` fields to keep them redacted when debug printed.
+    /// Field values are kept as they're stored, e.g. strings are not parsed into numbers.
+    ///
+    /// It's in `Layer::File`, and the modifiers of `.with_url(..)` apply to it, e.g. `.with_header("X-Vault-Token", token)` for token auth.
+    ///
+    /// Example:
+    /// ```rust,ignore
+    /// let cfg = Config::new()
+    ///     .with_vault("https://vault.example.com:8200", "secret", "app/db")
+    ///     .with_header("X-Vault-Token", &token)
+    ///     .build::<Cfg>();
+    /// ```
+    #[cfg(feature = "vault")]
+    pub fn with_vault(self, addr: &str, mount: &str, path: &str) -> Self {
+        Self {
+            config_impl: self.config_impl.with_vault(addr, mount, path, None),
+        }
+    }
+
+    /// Add configs from fields of a Vault KV v2 secret like `.with_vault(..)`, merged under dotted key path, e.g. `database.credentials`, requires `vault` feature.
+    #[cfg(feature = "vault")]
+    pub fn with_vault_at(self, addr: &str, mount: &str, path: &str, key: &str) -> Self {
+        Self {
+            config_impl: self.config_impl.with_vault(addr, mount, path, Some(key)),
+        }
+    }
+
+    /// Add configs from rows of a SQLite table, requires `sqlite` feature.
+    ///
+    /// Rows have `key` and `value` columns, and an optional `type` column: `string`, `integer`, `float`, `bool` or `json`.
+    /// Dotted keys map to nested key paths, e.g. `database.port` sets `port` inside `database`.
+    /// Text values without type are parsed into bool, integer, float or string, like env vars.
+    ///
+    /// It's in `Layer::File`. Use `SqliteWatcher` to reload when the database changes.
+    #[cfg(feature = "sqlite")]
+    pub fn with_sqlite(self, file_path: impl AsRef<Path>, table: &str) -> Self {
+        Self {
+            config_impl: self.config_impl.with_sqlite(file_path, table),
+        }
+    }
+
+    /// Add configs from rows of a SQLite query like `.with_sqlite(..)`, e.g. `SELECT name AS key, val AS value FROM settings WHERE device = 'edge-1'`, requires `sqlite` feature.
+    #[cfg(feature = "sqlite")]
+    pub fn with_sqlite_query(self, file_path: impl AsRef<Path>, query: &str) -> Self {
+        Self {
+            config_impl: self.config_impl.with_sqlite_query(file_path, query),
+        }
+    }
+
+    /// Decrypt encrypted values, `ENC[AES256_GCM,...]` strings from any source, with key read from file on build, requires `encryption` feature.
+    ///
+    /// The key is 32 bytes encoded in base64, e.g. from `generate_key()`. Values are encrypted by `encrypt_value(..)`.
+    /// Decryption is done after merging, failures are reported with the key path of the value, see `ConfigError::key_path()`.
+    ///
+    /// Example:
+    /// ```rust,ignore
+    /// // config.toml: password = "ENC[AES256_GCM,data:...,iv:...,tag:...,type:str]"
+    /// let cfg = Config::new()
+    ///     .with_toml("config.toml")
+    ///     .with_decryption_key_file("/run/secrets/config.key")
+    ///     .build::<Cfg>();
+    /// ```
+    #[cfg(feature = "encryption")]
+    pub fn with_decryption_key_file(self, file_path: impl AsRef<Path>) -> Self {
+        Self {
+            config_impl: self.config_impl.with_decryption_key_file(file_path),
+        }
+    }
+
+    /// Decrypt encrypted values like `.with_decryption_key_file(..)`, with key read from env var on build, looked up in `.with_env_vars(..)` first, requires `encryption` feature.
+    #[cfg(feature = "encryption")]
+    pub fn with_decryption_key_env(self, name: &str) -> Self {
+        Self {
+            config_impl: self.config_impl.with_decryption_key_env(name),
+        }
+    }
+
+    /// Add configs from a SOPS encrypted yaml file, requires `sops` feature.
+    ///
+    /// The data key of the file is decrypted with an age identity, from `.with_age_identity_file(..)`, else the file of `SOPS_AGE_KEY_FILE` env var,
+    /// else `sops/age/keys.txt` in the user config dir like sops, env vars looked up in `.with_env_vars(..)` first. Each `ENC[AES256_GCM,...]` value is decrypted,
+    /// and the MAC of the file is verified on build, so a file modified without the data key fails the build.
+    ///
+    /// It's in `Layer::File`, decryption works offline with local keys only.
+    ///
+    /// Example:
+    /// ```rust,ignore
+    /// let cfg = Config::new()
+    ///     .with_sops_yaml("secrets.enc.yaml")
+    ///     .with_age_identity_file("/run/secrets/age.txt")
+    ///     .build::<Cfg>();
+    /// ```
+    #[cfg(feature = "sops")]
+    pub fn with_sops_yaml(self, file_path: impl AsRef<Path>) -> Self {
+        Self {
+            config_impl: self.config_impl.with_sops_yaml(file_path),
+        }
+    }
+
+    /// Add configs from a SOPS encrypted json file like `.with_sops_yaml(..)`, requires `sops` feature.
+    #[cfg(feature = "sops")]
+    pub fn with_sops_json(self, file_path: impl AsRef<Path>) -> Self {
+        Self {
+            config_impl: self.config_impl.with_sops_json(file_path),
+        }
+    }
+
+    /// Age identity file decrypting the data keys of SOPS files, e.g. generated by `age-keygen`, requires `sops` feature.
+    #[cfg(feature = "sops")]
+    pub fn with_age_identity_file(self, file_path: impl AsRef<Path>) -> Self {
+        Self {
+            config_impl: self.config_impl.with_age_identity_file(file_path),
+        }
+    }
+
+    /// Validate the merged configs against a JSON Schema before deserializing, on each build, requires `schema` feature.
+    ///
+    /// Failures are reported with the JSON pointer of the value and the source supplying it, see `ConfigError::schema_violations()`.
+    /// Several schemas can be declared, the configs must be valid against all of them.
+    ///
+    /// Example:
+    /// ```rust,ignore
+    /// let cfg = Config::new()
+    ///     .with_yaml("config.yaml")
+    ///     .with_json_schema(json_schema::<Cfg>())
+    ///     .build::<Cfg>();
+    /// ```
+    #[cfg(feature = "schema")]
+    pub fn with_json_schema(self, schema: serde_json::Value) -> Self {
+        Self {
+            config_impl: self.config_impl.with_json_schema(schema),
+        }
+    }
+
+    /// Validate the merged configs against a JSON Schema like `.with_json_schema(..)`, with schema read from file on build, requires `schema` feature.
+    #[cfg(feature = "schema")]
+    pub fn with_json_schema_file(self, file_path: impl AsRef<Path>) -> Self {
+        Self {
+            config_impl: self.config_impl.with_json_schema_file(file_path),
+        }
+    }
+
+    /// Timeout of each request of the last declared remote source, no timeout by default.
+    #[cfg(feature = "http")]
+    pub fn with_timeout(self, timeout: std::time::Duration) -> Self {
+        Self {
+            config_impl: self.config_impl.with_timeout(timeout),
+        }
+    }
+
+    /// Retries of the last declared remote source when the server is unreachable or fails with 5xx status, with increasing delay between them.
+    #[cfg(feature = "http")]
+    pub fn with_retries(self, retries: u32) -> Self {
+        Self {
+            config_impl: self.config_impl.with_retries(retries),
+        }
+    }
+
+    /// Add header to requests of the last declared remote source, e.g. for auth.
+    #[cfg(feature = "http")]
+    pub fn with_header(self, name: &str, value: &str) -> Self {
+        Self {
+            config_impl: self.config_impl.with_header(name, value),
+        }
+    }
+
+    /// Cache the document of the last declared remote source on disk.
+    ///
+    /// Its ETag is kept next to the cache file, with `.etag` extension, and sent as `If-None-Match`, so the cached copy is used when the server answers `304 Not Modified`.
+    /// The cached copy is also used when the server is unreachable after retries.
+    ///
+    /// It fails the build for Vault sources, as their secrets would be stored in plain text.
+    #[cfg(feature = "http")]
+    pub fn with_cache(self, file_path: impl AsRef<Path>) -> Self {
+        Self {
+            config_impl: self.config_impl.with_cache(file_path),
+        }
+    }
+
+    /// Remove key at dotted key path set by any other layer, e.g. `.without_key("legacy.section")`
+    ///
+    /// Removed keys are in `Layer::Override`, use `.with_layer(..)` to remove keys of lower layers only.
+    /// Keys set by previous sources of the same layer are duplicates unless `.with_conflict(Conflict::LastWins)`.
+    /// Combined with `#[serde(default)]` or `Option<T>` fields, it resets fields to their default or `None`.
+    pub fn without_key(self, key: &str) -> Self {
+        Self {
+            config_impl: self.config_impl.without_key(key),
+        }
+    }
+
+    /// Add configs from async source, requires `async` feature.
+    ///
+    /// It's in `Layer::File` like files, and loaded by `.build_async()`, concurrently with other async sources.
+    #[cfg(feature = "async")]
+    pub fn with_async_source(self, source: impl AsyncSource + 'static) -> Self {
+        Self {
+            config_impl: self.config_impl.with_async_source(source),
+        }
+    }
+
+    /// Add configs from command-line arguments, e.g. `std::env::args()`
+    ///
+    /// - `--database.port=5432` sets `port` inside `database`, using the same nested key paths as json, toml and yaml,
+    /// - `--feature-x` sets `feature-x` to `true`,
+    /// - `--no-feature-x` sets `feature-x` to `false`,
+    /// - repeated flags, e.g. `--host=a --host=b`, form an array, use `.with_args_list("host")` for an array of a flag given once,
+    /// - repeated boolean flags keep the last one, e.g. `--feature-x --no-feature-x` sets `feature-x` to `false`.
+    ///
+    /// Arguments not starting with `--`, e.g. program name, are ignored, and parsing stops at `--`.
+    ///
+    /// Command-line arguments are in `Layer::Override`, overwriting values from every other layer.
+    pub fn with_args<I, S>(self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            config_impl: self.config_impl.with_args(args),
+        }
+    }
+
+    /// Parse argument at dotted key path into array even when it's given once, e.g. `--hosts=a` into `["a"]` like `--hosts=a --hosts=b` into `["a", "b"]`
+    ///
+    /// So a `Vec<T>` field deserializes however many times the flag is given. It applies to `.with_args(..)` declared before or after it.
+    pub fn with_args_list(self, key: &str) -> Self {
+        Self {
+            config_impl: self.config_impl.with_args_list(key),
+        }
+    }
+
+    /// Add configs from arguments parsed by [clap](https://docs.rs/clap), requires `clap` feature.
+    ///
+    /// Only arguments explicitly supplied on command-line are added, so clap default values don't shadow values from files or env.
+    ///
+    /// Argument ids map to nested key paths, e.g. id `database.port` sets `port` inside `database`, and arguments of subcommands are nested under the subcommand name.
+    /// Arguments accepting multiple values are added as arrays.
+    ///
+    /// Like `.with_args(..)`, it's in `Layer::Override`: defaults < files < env < command-line.
+    ///
+    /// Example:
+    /// ```rust,ignore
+    /// use clap::{CommandFactory, FromArgMatches, Parser};
+    ///
+    /// #[derive(Parser)]
+    /// struct Cli {
+    ///     #[arg(long, id = "database.port", default_value_t = 5432)]
+    ///     port: u16,
+    /// }
+    ///
+    /// let command = Cli::command();
+    /// let matches = command.clone().get_matches();
+    /// let cli = Cli::from_arg_matches(&matches)?;
+    /// let cfg = Config::new().with_json("config.json").with_clap(&command, &matches).build::<Cfg>();
+    /// ```
+    #[cfg(feature = "clap")]
+    pub fn with_clap(self, command: &clap::Command, matches: &clap::ArgMatches) -> Self {
+        Self {
+            config_impl: self.config_impl.with_clap(command, matches),
+        }
+    }
+
+    /// Declared sources in declaration order, without reading them.
+    ///
+    /// Example:
+    /// ```rust
+    /// use configrs::config::{Config, Layer, SourceKind};
+    ///
+    /// let config = Config::new().with_json("config.json").with_layer(Layer::Defaults);
+    /// let sources = config.sources();
+    /// assert_eq!(sources[0].kind, SourceKind::OsEnv);
+    /// assert_eq!(sources[1].layer, Layer::Defaults);
+    /// ```
+    pub fn sources(&self) -> Vec<SourceInfo> {
+        self.config_impl.sources()
+    }
+
     /// Build configs into T
     ///
     /// This utilize serde DeserializeOwned type, so T must be implemented/derived the Deserialize and/or Serialize trait from serde.
     ///
     /// Use serde to alias or rename config fields as well as for default values or flattening the structure.
-    pub fn build<T>(self) -> Result<T, ConfigError>
+    ///
+    /// It doesn't consume the builder, so `build` can be called repeatedly, picking up changes of files and env vars.
+    /// Sources are read on each build, so to build a typed config per module, read them once with `.load()` and build from the snapshot.
+    /// Unknown fields are ignored by serde unless denied, so each type can pick only the keys it needs.
+    ///
+    /// Example:
+    /// ```rust
+    /// use configrs::config::Config;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Database {}
+    /// #[derive(Debug, Deserialize)]
+    /// struct Server {}
+    ///
+    /// let config = Config::new().with_json("config.json").with_toml("config.toml");
+    /// let database = config.build::<Database>();
+    /// ```
+    pub fn build<T>(&self) -> Result<T, ConfigError>
     where
         T: DeserializeOwned + Debug,
     {
         Ok(self.config_impl.build::<T>()?)
     }
+
+    /// Read and merge sources once into a snapshot, building many types without reading sources again.
+    ///
+    /// Files, remote sources and env vars are read here only, so changes after it are picked up by loading again.
+    ///
+    /// Example:
+    /// ```rust
+    /// use configrs::config::Config;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Database {}
+    /// #[derive(Debug, Deserialize)]
+    /// struct Server {}
+    ///
+    /// let config = Config::new().with_json("config.json").with_toml("config.toml");
+    /// if let Ok(loaded) = config.load() {
+    ///     let database = loaded.build::<Database>();
+    ///     let server = loaded.build::<Server>();
+    /// }
+    /// ```
+    pub fn load(&self) -> Result<Loaded, ConfigError> {
+        Ok(Loaded {
+            loaded_impl: self.config_impl.load()?,
+        })
+    }
+
+    /// Build configs into T, loading async sources concurrently, requires `async` feature.
+    ///
+    /// Sync and async sources are merged in the same precedence chain. Building config with async sources by `.build()` fails.
+    ///
+    /// Example:
+    /// ```rust,ignore
+    /// let cfg = Config::new()
+    ///     .with_json("config.json")
+    ///     .with_async_source(Remote)
+    ///     .with_layer(Layer::Env)
+    ///     .build_async::<Cfg>()
+    ///     .await?;
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn build_async<T>(&self) -> Result<T, ConfigError>
+    where
+        T: DeserializeOwned + Debug,
+    {
+        Ok(self.config_impl.build_async::<T>().await?)
+    }
+
+    /// Build subtree at dotted key path into T, e.g. `.build_at::<Billing>("services.billing")`
+    ///
+    /// Lets a library own one section of the config without knowing the root type. Fails if there is no value at the key path.
+    pub fn build_at<T>(&self, key: &str) -> Result<T, ConfigError>
+    where
+        T: DeserializeOwned + Debug,
+    {
+        Ok(self.config_impl.build_at::<T>(key)?)
+    }
+
+    /// Build configs into T, then check its rules of `Validate`, e.g. port ranges, non-empty strings or either of two keys set.
+    ///
+    /// All failures are returned in one error with their key paths, see `ConfigError::validation_errors()`.
+    ///
+    /// Example:
+    /// ```rust,ignore
+    /// let cfg = Config::new().with_toml("config.toml").build_validated::<Cfg>();
+    /// if let Err(err) = &cfg {
+    ///     for failure in err.validation_errors() {
+    ///         eprintln!("{}: {}", failure.key_path, failure.message);
+    ///     }
+    /// }
+    /// ```
+    pub fn build_validated<T>(&self) -> Result<T, ConfigError>
+    where
+        T: DeserializeOwned + Debug + Validate,
+    {
+        Ok(self.config_impl.build_validated::<T>()?)
+    }
+
+    /// Build configs into T, validating the merged configs against the JSON Schema of T first, and declared schemas if any, requires `schema` feature.
+    ///
+    /// Unlike serde errors, all failures are reported with the source of each value, see `ConfigError::schema_violations()`.
+    #[cfg(feature = "schema")]
+    pub fn build_with_schema<T>(&self) -> Result<T, ConfigError>
+    where
+        T: DeserializeOwned + Debug + schemars::JsonSchema,
+    {
+        Ok(self.config_impl.build_with_schema::<T>()?)
+    }
+
+    /// Wait for changes of Consul sources with blocking queries, requires `consul` feature.
+    ///
+    /// Returns the new index once any Consul source changes after index, or index itself when wait has elapsed without changes.
+    /// Index `0` returns the current index right away.
+    ///
+    /// Example:
+    /// ```rust,ignore
+    /// let config = Config::new().with_consul("http://127.0.0.1:8500", "app");
+    /// let mut index = 0;
+    /// loop {
+    ///     index = config.watch_consul(index, Duration::from_secs(60))?;
+    ///     let cfg = config.build::<Cfg>()?;
+    ///     // apply cfg
+    /// }
+    /// ```
+    #[cfg(feature = "consul")]
+    pub fn watch_consul(&self, index: u64, wait: std::time::Duration) -> Result<u64, ConfigError> {
+        Ok(self.config_impl.watch_consul(index, wait)?)
+    }
+
+    /// Wait for changes of etcd sources with watch requests, requires `etcd` feature.
+    ///
+    /// Returns the revision of the first change of any etcd source after revision, or revision itself when wait has elapsed without changes.
+    /// Revision `0` returns the current revision right away.
+    ///
+    /// Example:
+    /// ```rust,ignore
+    /// let config = Config::new().with_etcd("http://127.0.0.1:2379", "/app");
+    /// let mut revision = 0;
+    /// loop {
+    ///     revision = config.watch_etcd(revision, Duration::from_secs(60))?;
+    ///     let cfg = config.build::<Cfg>()?;
+    ///     // apply cfg
+    /// }
+    /// ```
+    #[cfg(feature = "etcd")]
+    pub fn watch_etcd(&self, revision: u64, wait: std::time::Duration) -> Result<u64, ConfigError> {
+        Ok(self.config_impl.watch_etcd(revision, wait)?)
+    }
+
+    /// Lease and version of Vault secrets, read again from Vault, requires `vault` feature.
+    ///
+    /// Use it to reload when a secret has a new version, or before its lease expires.
+    #[cfg(feature = "vault")]
+    pub fn vault_metadata(&self) -> Result<Vec<VaultMetadata>, ConfigError> {
+        Ok(self.config_impl.vault_metadata()?)
+    }
+}
+
+/// Merged configs of sources read once by `Config::load()`.
+///
+/// Building from it doesn't read sources again, so one snapshot can be built into a typed config per module.
+#[derive(Clone)]
+pub struct Loaded {
+    loaded_impl: config_impl::LoadedImpl,
+}
+
+impl Loaded {
+    /// Build configs into T, like `Config::build()`.
+    pub fn build<T>(&self) -> Result<T, ConfigError>
+    where
+        T: DeserializeOwned + Debug,
+    {
+        Ok(self.loaded_impl.build::<T>()?)
+    }
+
+    /// Build subtree at dotted key path into T, like `Config::build_at()`.
+    pub fn build_at<T>(&self, key: &str) -> Result<T, ConfigError>
+    where
+        T: DeserializeOwned + Debug,
+    {
+        Ok(self.loaded_impl.build_at::<T>(key)?)
+    }
 }
@@ -30,9 +30,36 @@ pub(super) enum ConfigErrorImpl {
     DuplicateKey(String),
     FileError(String),
     JsonError(String),
+    Json5Error(String),
     YamlError(String),
     TomlError(String),
+    RonError(String),
+    ArgsError(String),
+    EnvError(String),
+    #[cfg(feature = "async")]
+    SourceError(String),
+    #[cfg(feature = "http")]
+    HttpError(String),
+    #[cfg(feature = "http")]
+    RemoteOptionError(String),
+    #[cfg(feature = "consul")]
+    ConsulError(String),
+    #[cfg(feature = "etcd")]
+    EtcdError(String),
+    #[cfg(feature = "vault")]
+    VaultError(String),
+    #[cfg(feature = "sqlite")]
+    SqliteError(String),
+    #[cfg(feature = "encryption")]
+    DecryptionError(String, String), // key path, empty if the error is not about a value
+    #[cfg(feature = "encryption")]
+    EncryptionError(String),
+    #[cfg(feature = "sops")]
+    SopsError(String),
     BuildError(String),
+    ValidationError(Vec<super::ValidationError>),
+    #[cfg(feature = "schema")]
+    SchemaError(Vec<super::SchemaViolation>),
 
     // serde
     SerdeError(String),
@@ -40,6 +67,33 @@ pub(super) enum ConfigErrorImpl {
 
 impl ConfigErrorImpl {
     pub const CONFIG_ERROR_IMPL_SERDE_PREFIX: &'static str = "[CONFIG][ERROR][SERDE]";
+
+    pub fn key_path(&self) -> Option<&str> {
+        match self {
+            #[cfg(feature = "encryption")]
+            ConfigErrorImpl::DecryptionError(path, _) if !path.is_empty() => Some(path),
+            ConfigErrorImpl::ValidationError(errors) => errors
+                .first()
+                .map(|e| e.key_path.as_str())
+                .filter(|path| !path.is_empty()),
+            _ => None,
+        }
+    }
+
+    pub fn validation_errors(&self) -> &[super::ValidationError] {
+        match self {
+            ConfigErrorImpl::ValidationError(errors) => errors,
+            _ => &[],
+        }
+    }
+
+    #[cfg(feature = "schema")]
+    pub fn schema_violations(&self) -> &[super::SchemaViolation] {
+        match self {
+            ConfigErrorImpl::SchemaError(violations) => violations,
+            _ => &[],
+        }
+    }
 }
 
 impl Display for ConfigErrorImpl {
@@ -54,15 +108,92 @@ impl Display for ConfigErrorImpl {
             ConfigErrorImpl::JsonError(v) => {
                 write!(f, "Json parsing error: {}", v)
             }
+            ConfigErrorImpl::Json5Error(v) => {
+                write!(f, "Json5 parsing error: {}", v)
+            }
             ConfigErrorImpl::YamlError(v) => {
                 write!(f, "Yaml parsing error: {}", v)
             }
             ConfigErrorImpl::TomlError(v) => {
                 write!(f, "Toml parsing error: {}", v)
             }
+            ConfigErrorImpl::RonError(v) => {
+                write!(f, "Ron parsing error: {}", v)
+            }
+            ConfigErrorImpl::ArgsError(v) => {
+                write!(f, "Command-line arguments error: {}", v)
+            }
+            ConfigErrorImpl::EnvError(v) => {
+                write!(f, "Env parsing error: {}", v)
+            }
+            #[cfg(feature = "async")]
+            ConfigErrorImpl::SourceError(v) => {
+                write!(f, "Source error: {}", v)
+            }
+            #[cfg(feature = "http")]
+            ConfigErrorImpl::HttpError(v) => {
+                write!(f, "Http error: {}", v)
+            }
+            #[cfg(feature = "http")]
+            ConfigErrorImpl::RemoteOptionError(v) => {
+                write!(f, "Remote source option error: {}", v)
+            }
+            #[cfg(feature = "consul")]
+            ConfigErrorImpl::ConsulError(v) => {
+                write!(f, "Consul error: {}", v)
+            }
+            #[cfg(feature = "etcd")]
+            ConfigErrorImpl::EtcdError(v) => {
+                write!(f, "Etcd error: {}", v)
+            }
+            #[cfg(feature = "vault")]
+            ConfigErrorImpl::VaultError(v) => {
+                write!(f, "Vault error: {}", v)
+            }
+            #[cfg(feature = "sqlite")]
+            ConfigErrorImpl::SqliteError(v) => {
+                write!(f, "Sqlite error: {}", v)
+            }
+            #[cfg(feature = "encryption")]
+            ConfigErrorImpl::DecryptionError(path, v) if path.is_empty() => {
+                write!(f, "Decryption error: {}", v)
+            }
+            #[cfg(feature = "encryption")]
+            ConfigErrorImpl::DecryptionError(path, v) => {
+                write!(f, "Decryption error at {}: {}", path, v)
+            }
+            #[cfg(feature = "encryption")]
+            ConfigErrorImpl::EncryptionError(v) => {
+                write!(f, "Encryption error: {}", v)
+            }
+            #[cfg(feature = "sops")]
+            ConfigErrorImpl::SopsError(v) => {
+                write!(f, "Sops error: {}", v)
+            }
             ConfigErrorImpl::BuildError(v) => {
                 write!(f, "Failed building config: {}", v)
             }
+            ConfigErrorImpl::ValidationError(errors) => {
+                let errors = errors
+                    .iter()
+                    .map(|e| match e.key_path.as_str() {
+                        "" => e.message.clone(),
+                        path => format!("{}: {}", path, e.message),
+                    })
+                    .collect::<Vec<_>>();
+                write!(f, "Validation failed: {}", errors.join(", "))
+            }
+            #[cfg(feature = "schema")]
+            ConfigErrorImpl::SchemaError(violations) => {
+                let violations = violations
+                    .iter()
+                    .map(|v| match &v.source {
+                        Some(source) => format!("{}: {} (from {})", v.pointer, v.message, source),
+                        None => format!("{}: {}", v.pointer, v.message),
+                    })
+                    .collect::<Vec<_>>();
+                write!(f, "Schema validation failed: {}", violations.join(", "))
+            }
             ConfigErrorImpl::SerdeError(v) => {
                 write!(f, "Failed parsing error into serde: {}", v)
             }
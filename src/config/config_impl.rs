@@ -1,39 +1,176 @@
 // TODO:
 // - Refactor: make illegal states unrepresentable
 
+use serde::Deserialize;
 use serde::de::DeserializeOwned;
 use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::error::Error;
 use std::fmt::Debug;
 use std::path::Path;
+#[cfg(feature = "async")]
+use std::sync::Arc;
 
+#[cfg(feature = "async")]
+use super::AsyncSource;
+#[cfg(feature = "schema")]
+use super::SchemaViolation;
+#[cfg(feature = "vault")]
+use super::VaultMetadata;
 use super::config_error_impl::ConfigErrorImpl;
+#[cfg(feature = "consul")]
+use super::consul_impl;
+#[cfg(feature = "encryption")]
+use super::encryption_impl;
+#[cfg(feature = "etcd")]
+use super::etcd_impl;
+#[cfg(feature = "http")]
+use super::http_impl;
+#[cfg(feature = "schema")]
+use super::schema_impl;
+#[cfg(feature = "sops")]
+use super::sops_impl;
+#[cfg(feature = "sqlite")]
+use super::sqlite_impl;
+#[cfg(feature = "vault")]
+use super::vault_impl;
+use super::{ArrayMerge, Conflict, Format, Layer, SourceInfo, SourceKind, Validate, Validator};
+
+// RON is read through an untagged enum so that ron keeps enum variants in the single-key table form,
+// e.g. `Custom(5)` into `{"Custom": 5}` and `Fast` into `"Fast"`, instead of dropping the variant names.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RonValue {
+    Value(serde_json::Value),
+}
+
+// value at key path, none to remove the key.
+type Entry = (Vec<String>, Option<serde_json::Value>);
+
+// a declared source, read in build. Values given in memory are kept with it, e.g. values, injected env vars and arguments.
+#[derive(Clone)]
+struct Declared {
+    info: SourceInfo,
+    values: serde_json::Map<String, serde_json::Value>,
+    #[cfg(feature = "async")]
+    async_source: Option<Arc<dyn AsyncSource>>, // not loaded yet
+    #[cfg(feature = "http")]
+    http: http_impl::HttpOptions,
+}
+
+// a loaded source with its values as key paths, e.g. top level keys for files and nested key paths for command-line arguments.
+struct Source {
+    #[cfg(feature = "schema")]
+    kind: SourceKind, // reported with schema violations
+    layer: Layer,
+    conflict: Conflict,
+    origin: usize,  // index of the declared source, shared by documents of a yaml file
+    prefixed: bool, // filtered with prefix, for env vars, .env, values
+    env: bool,      // raw strings of env vars and .env, parsed in build
+    entries: Vec<Entry>,
+    spellings: Vec<String>, // original spelling of entries with normalized keys
+}
+
+// parsing of env var value into array or map.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EnvParse {
+    List,
+    Map,
+    Indexed,
+}
+
+// key of encrypted values, read on build.
+#[cfg(feature = "encryption")]
+#[derive(Clone)]
+enum DecryptionKey {
+    File(std::path::PathBuf),
+    Env(String),
+}
+
+// JSON Schema of merged configs, read on build.
+#[cfg(feature = "schema")]
+#[derive(Clone)]
+enum Schema {
+    Value(serde_json::Value),
+    File(std::path::PathBuf),
+}
+
+// merged configs of sources read once, built into many types without reading sources again.
+#[derive(Clone)]
+pub(super) struct LoadedImpl {
+    values: serde_json::Map<String, serde_json::Value>,
+    normalize_keys: bool, // key paths of build_at are normalized like the merged keys
+}
+
+impl LoadedImpl {
+    pub fn build<T>(&self) -> Result<T, ConfigErrorImpl>
+    where
+        T: DeserializeOwned + Debug,
+    {
+        Ok(serde_json::from_value::<T>(serde_json::Value::Object(
+            self.values.clone(),
+        ))?)
+    }
+
+    pub fn build_at<T>(&self, key: &str) -> Result<T, ConfigErrorImpl>
+    where
+        T: DeserializeOwned + Debug,
+    {
+        let value = ConfigImpl::subtree(self.values.clone(), key, self.normalize_keys)?;
+        Ok(serde_json::from_value::<T>(value)?)
+    }
+}
 
 #[derive(Clone, Default)]
 pub(super) struct ConfigImpl {
-    env: serde_json::Map<String, serde_json::Value>, // for env vars, .env, values
-    files_env: serde_json::Map<String, serde_json::Value>, // for json, yaml, toml
+    declared: Vec<Declared>,
     prefix: &'static str,
-    overwrite: bool,
+    conflict: Conflict, // conflict policy for next declared sources
+    array_merge: ArrayMerge,
+    array_merge_keys: HashMap<String, ArrayMerge>, // array merge strategies by dotted key path
+    env_parse: HashMap<String, EnvParse>,          // env var names parsed into arrays or maps
+    env_separator: Option<char>, // separator of lists and maps items, `,` by default
+    args_lists: Vec<String>,     // dotted key paths of arguments parsed into arrays when given once
+    normalize_keys: bool,
+    raw_keys: Vec<Vec<String>>, // normalized key paths of tables whose keys are kept as-is
+    #[cfg(feature = "encryption")]
+    decryption_key: Option<DecryptionKey>,
+    #[cfg(feature = "sops")]
+    age_identity: Option<std::path::PathBuf>, // identity file of SOPS data keys
+    #[cfg(feature = "schema")]
+    schemas: Vec<Schema>,
     err: Option<ConfigErrorImpl>,
 }
 
 impl ConfigImpl {
     pub fn new() -> Self {
-        let env: serde_json::Map<String, serde_json::Value> = env::vars()
+        Self::empty().declare(SourceKind::OsEnv, Layer::Env, serde_json::Map::new())
+    }
+
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    pub fn with_env_vars<I, K, V>(self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        // check error
+        if self.err.is_some() {
+            return self;
+        }
+
+        let vars = vars
             .into_iter()
-            .map(|(key, val)| (key, Self::parse_str(&val)))
+            .map(|(key, val)| (key.into(), serde_json::Value::String(val.into())))
             .collect();
-        let files_env = serde_json::Map::new();
-        Self {
-            env,
-            files_env,
-            ..Default::default()
-        }
+        self.declare(SourceKind::EnvVars, Layer::Env, vars)
     }
 
-    pub fn with_value<V>(mut self, key: &str, value: V) -> Self
+    pub fn with_value<V>(self, key: &str, value: V) -> Self
     where
         V: Into<super::Value> + Debug,
     {
@@ -42,19 +179,69 @@ impl ConfigImpl {
             return self;
         }
 
-        // check duplicate if not overwrite
-        if !self.overwrite && Self::is_exist(&self.env, key) {
-            return Self::make_err(ConfigErrorImpl::DuplicateKey(key.to_string()));
+        let mut values = serde_json::Map::new();
+        values.insert(key.into(), serde_json::Value::from(value.into()));
+
+        self.declare(SourceKind::Value(key.to_string()), Layer::Env, values)
+    }
+
+    pub fn with_overwrite(mut self) -> Self {
+        self.conflict = Conflict::LastWins;
+        self
+    }
+
+    pub fn with_layer(mut self, layer: Layer) -> Self {
+        if let Some(declared) = self.declared.last_mut() {
+            declared.info.layer = layer;
         }
+        self
+    }
+
+    pub fn with_conflict(mut self, conflict: Conflict) -> Self {
+        if let Some(declared) = self.declared.last_mut() {
+            declared.info.conflict = conflict;
+        }
+        self
+    }
 
-        self.env
-            .insert(key.into(), serde_json::Value::from(value.into()));
+    pub fn with_array_merge(mut self, array_merge: ArrayMerge) -> Self {
+        self.array_merge = array_merge;
+        self
+    }
 
+    pub fn with_array_merge_at(mut self, key: &str, array_merge: ArrayMerge) -> Self {
+        self.array_merge_keys.insert(key.to_string(), array_merge);
         self
     }
 
-    pub fn with_overwrite(mut self) -> Self {
-        self.overwrite = true;
+    pub fn with_env_list(mut self, key: &str) -> Self {
+        self.env_parse.insert(key.to_string(), EnvParse::List);
+        self
+    }
+
+    pub fn with_env_map(mut self, key: &str) -> Self {
+        self.env_parse.insert(key.to_string(), EnvParse::Map);
+        self
+    }
+
+    pub fn with_env_indexed(mut self, key: &str) -> Self {
+        self.env_parse.insert(key.to_string(), EnvParse::Indexed);
+        self
+    }
+
+    pub fn with_env_separator(mut self, separator: char) -> Self {
+        self.env_separator = Some(separator);
+        self
+    }
+
+    pub fn with_normalized_keys(mut self) -> Self {
+        self.normalize_keys = true;
+        self
+    }
+
+    pub fn with_raw_keys_at(mut self, key: &str) -> Self {
+        self.raw_keys
+            .push(key.split('.').map(Self::normalize_key).collect());
         self
     }
 
@@ -63,186 +250,1634 @@ impl ConfigImpl {
         self
     }
 
-    pub fn with_env(mut self, file_path: impl AsRef<Path>) -> Self {
+    pub fn with_env(self, file_path: impl AsRef<Path>) -> Self {
+        self.declare_file(SourceKind::EnvFile(file_path.as_ref().to_path_buf()))
+    }
+
+    pub fn with_json(self, file_path: impl AsRef<Path>) -> Self {
+        self.declare_file(SourceKind::Json(file_path.as_ref().to_path_buf()))
+    }
+
+    pub fn with_json5(self, file_path: impl AsRef<Path>) -> Self {
+        self.declare_file(SourceKind::Json5(file_path.as_ref().to_path_buf()))
+    }
+
+    pub fn with_ron(self, file_path: impl AsRef<Path>) -> Self {
+        self.declare_file(SourceKind::Ron(file_path.as_ref().to_path_buf()))
+    }
+
+    pub fn with_toml(self, file_path: impl AsRef<Path>) -> Self {
+        self.declare_file(SourceKind::Toml(file_path.as_ref().to_path_buf()))
+    }
+
+    pub fn with_yaml(self, file_path: impl AsRef<Path>) -> Self {
+        self.declare_file(SourceKind::Yaml(file_path.as_ref().to_path_buf()))
+    }
+
+    pub fn without_key(self, key: &str) -> Self {
         // check error
         if self.err.is_some() {
             return self;
         }
 
-        let env_map = env_file_reader::read_file(&file_path).map_or_else(
-            |err| {
-                return Self::make_err(Self::make_err_open_file(&file_path, err));
-            },
-            |env_map_iter| {
-                for (key, val) in env_map_iter {
-                    // check duplicate if not overwrite
-                    if !self.overwrite && Self::is_exist(&self.env, &key) {
-                        return Self::make_err(ConfigErrorImpl::DuplicateKey(key));
-                    }
-                    let value = Self::parse_str(&val);
-                    self.env.insert(key, value);
+        self.declare(
+            SourceKind::Unset(key.to_string()),
+            Layer::Override,
+            serde_json::Map::new(),
+        )
+    }
+
+    pub fn with_args<I, S>(self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        // check error
+        if self.err.is_some() {
+            return self;
+        }
+
+        let mut values = serde_json::Map::new();
+        for arg in args {
+            let arg = arg.as_ref();
+            // `--` ends the options
+            if arg == "--" {
+                break;
+            }
+            // program name and positional arguments
+            let Some(arg) = arg.strip_prefix("--") else {
+                continue;
+            };
+            let (key, value) = match arg.split_once('=') {
+                Some((key, value)) => (key, Self::parse_str(value)),
+                None => {
+                    let (key, value) = match arg.strip_prefix("no-") {
+                        Some(key) => (key, serde_json::Value::Bool(false)),
+                        None => (arg, serde_json::Value::Bool(true)),
+                    };
+                    // last flag wins, e.g. `--x --no-x` is false, instead of forming an array
+                    let path = key.split('.').map(String::from).collect::<Vec<_>>();
+                    Self::unset_path(&mut values, &path);
+                    (key, value)
                 }
+            };
+            if let Err(err) = Self::insert_path(&mut values, key, value) {
+                return Self::make_err(err);
+            }
+        }
 
-                return self;
-            },
-        );
+        self.declare(SourceKind::Args, Layer::Override, values)
+    }
 
-        env_map
+    pub fn with_args_list(mut self, key: &str) -> Self {
+        self.args_lists.push(key.to_string());
+        self
     }
 
-    pub fn with_json(mut self, file_path: impl AsRef<Path>) -> Self {
+    #[cfg(feature = "clap")]
+    pub fn with_clap(self, command: &clap::Command, matches: &clap::ArgMatches) -> Self {
         // check error
         if self.err.is_some() {
             return self;
         }
 
-        let ret = std::fs::File::open(file_path).map_or_else(
-            |err| {
-                return Self::make_err(ConfigErrorImpl::FileError(err.to_string()));
-            },
-            |file| {
-                let reader = std::io::BufReader::new(file);
-                serde_json::from_reader::<_, serde_json::Value>(reader).map_or_else(
-                    |err| {
-                        return Self::make_err(ConfigErrorImpl::JsonError(err.to_string()));
-                    },
-                    |json_value| {
-                        if let serde_json::Value::Object(v) = json_value {
-                            for (key, val) in v {
-                                // check duplicate if not overwrite
-                                if !self.overwrite && Self::is_exist(&self.files_env, &key) {
-                                    return Self::make_err(ConfigErrorImpl::DuplicateKey(key));
-                                }
-                                self.files_env.insert(key, val);
-                            }
-                        }
-                        return self;
-                    },
-                )
-            },
-        );
+        let mut values = serde_json::Map::new();
+        if let Err(err) = Self::insert_arg_matches(&mut values, "", command, matches) {
+            return Self::make_err(err);
+        }
 
+        self.declare(SourceKind::Clap, Layer::Override, values)
+    }
+
+    #[cfg(feature = "async")]
+    pub fn with_async_source(self, source: impl AsyncSource + 'static) -> Self {
+        // check error
+        if self.err.is_some() {
+            return self;
+        }
+
+        let mut ret = self.declare(
+            SourceKind::Async(source.name()),
+            Layer::File,
+            serde_json::Map::new(),
+        );
+        if let Some(declared) = ret.declared.last_mut() {
+            declared.async_source = Some(Arc::new(source));
+        }
         ret
     }
 
-    pub fn with_toml(mut self, file_path: impl AsRef<Path>) -> Self {
+    #[cfg(feature = "http")]
+    pub fn with_url(self, url: &str, format: Format) -> Self {
         // check error
         if self.err.is_some() {
             return self;
         }
 
-        let ret = Self::load_file_to_string(file_path).map_or_else(
-            move |err| Self::make_err(err),
-            move |s| {
-                let ret = toml::from_str::<toml::Value>(&s).map_or_else(
-                    move |err| Self::make_err(ConfigErrorImpl::TomlError(err.to_string())),
-                    move |d| {
-                        let jsoned = json!(d);
-                        if let serde_json::Value::Object(v) = jsoned {
-                            for (key, val) in v {
-                                // check duplicate if not overwrite
-                                if !self.overwrite && Self::is_exist(&self.files_env, &key) {
-                                    return Self::make_err(ConfigErrorImpl::DuplicateKey(key));
-                                }
-                                self.files_env.insert(key, val);
-                            }
-                        }
+        self.declare(
+            SourceKind::Url(url.to_string(), format),
+            Layer::File,
+            serde_json::Map::new(),
+        )
+    }
 
-                        self
-                    },
-                );
-                ret
-            },
-        );
+    #[cfg(feature = "consul")]
+    pub fn with_consul(self, addr: &str, prefix: &str) -> Self {
+        // check error
+        if self.err.is_some() {
+            return self;
+        }
 
-        ret
+        self.declare(
+            SourceKind::Consul(addr.to_string(), prefix.to_string()),
+            Layer::File,
+            serde_json::Map::new(),
+        )
     }
 
-    pub fn with_yaml(mut self, file_path: impl AsRef<Path>) -> Self {
+    #[cfg(feature = "etcd")]
+    pub fn with_etcd(self, addr: &str, prefix: &str) -> Self {
         // check error
         if self.err.is_some() {
             return self;
         }
 
-        let ret = Self::load_file_to_string(file_path).map_or_else(
-            |err| Self::make_err(err),
-            |val| {
-                let ret = serde_yaml::from_str::<serde_json::Value>(&val).map_or_else(
-                    |err| Self::make_err(ConfigErrorImpl::YamlError(err.to_string())),
-                    |val| {
-                        if let serde_json::Value::Object(v) = val {
-                            for (key, val) in v {
-                                // check duplicate if not overwrite
-                                if !self.overwrite && Self::is_exist(&self.files_env, &key) {
-                                    return Self::make_err(ConfigErrorImpl::DuplicateKey(key));
-                                }
-                                self.files_env.insert(key, val);
-                            }
-                        }
-                        self
-                    },
-                );
-                ret
-            },
-        );
+        self.declare(
+            SourceKind::Etcd(addr.to_string(), prefix.to_string()),
+            Layer::File,
+            serde_json::Map::new(),
+        )
+    }
 
-        ret
+    #[cfg(feature = "vault")]
+    pub fn with_vault(self, addr: &str, mount: &str, path: &str, key: Option<&str>) -> Self {
+        // check error
+        if self.err.is_some() {
+            return self;
+        }
+
+        self.declare(
+            SourceKind::Vault(vault_impl::url(addr, mount, path), key.map(String::from)),
+            Layer::File,
+            serde_json::Map::new(),
+        )
+    }
+
+    #[cfg(feature = "sqlite")]
+    pub fn with_sqlite(self, file_path: impl AsRef<Path>, table: &str) -> Self {
+        self.with_sqlite_query(file_path, &sqlite_impl::table_query(table))
+    }
+
+    #[cfg(feature = "sqlite")]
+    pub fn with_sqlite_query(self, file_path: impl AsRef<Path>, query: &str) -> Self {
+        // check error
+        if self.err.is_some() {
+            return self;
+        }
+
+        self.declare(
+            SourceKind::Sqlite(file_path.as_ref().to_path_buf(), query.to_string()),
+            Layer::File,
+            serde_json::Map::new(),
+        )
+    }
+
+    #[cfg(feature = "encryption")]
+    pub fn with_decryption_key_file(mut self, file_path: impl AsRef<Path>) -> Self {
+        self.decryption_key = Some(DecryptionKey::File(file_path.as_ref().to_path_buf()));
+        self
+    }
+
+    #[cfg(feature = "encryption")]
+    pub fn with_decryption_key_env(mut self, name: &str) -> Self {
+        self.decryption_key = Some(DecryptionKey::Env(name.to_string()));
+        self
+    }
+
+    #[cfg(feature = "sops")]
+    pub fn with_sops_yaml(self, file_path: impl AsRef<Path>) -> Self {
+        self.declare_file(SourceKind::Sops(
+            file_path.as_ref().to_path_buf(),
+            Format::Yaml,
+        ))
+    }
+
+    #[cfg(feature = "sops")]
+    pub fn with_sops_json(self, file_path: impl AsRef<Path>) -> Self {
+        self.declare_file(SourceKind::Sops(
+            file_path.as_ref().to_path_buf(),
+            Format::Json,
+        ))
+    }
+
+    #[cfg(feature = "sops")]
+    pub fn with_age_identity_file(mut self, file_path: impl AsRef<Path>) -> Self {
+        self.age_identity = Some(file_path.as_ref().to_path_buf());
+        self
+    }
+
+    #[cfg(feature = "schema")]
+    pub fn with_json_schema(mut self, schema: serde_json::Value) -> Self {
+        self.schemas.push(Schema::Value(schema));
+        self
+    }
+
+    #[cfg(feature = "schema")]
+    pub fn with_json_schema_file(mut self, file_path: impl AsRef<Path>) -> Self {
+        self.schemas
+            .push(Schema::File(file_path.as_ref().to_path_buf()));
+        self
+    }
+
+    #[cfg(feature = "http")]
+    pub fn with_timeout(self, timeout: std::time::Duration) -> Self {
+        self.with_http_option("with_timeout", |http| http.timeout = Some(timeout))
+    }
+
+    #[cfg(feature = "http")]
+    pub fn with_retries(self, retries: u32) -> Self {
+        self.with_http_option("with_retries", |http| http.retries = retries)
+    }
+
+    #[cfg(feature = "http")]
+    pub fn with_header(self, name: &str, value: &str) -> Self {
+        self.with_http_option("with_header", |http| {
+            http.headers.push((name.to_string(), value.to_string()))
+        })
+    }
+
+    #[cfg(feature = "http")]
+    pub fn with_cache(self, file_path: impl AsRef<Path>) -> Self {
+        // secrets would be stored in plain text
+        #[cfg(feature = "vault")]
+        if self.err.is_none()
+            && let Some(declared) = self.declared.last()
+            && matches!(declared.info.kind, SourceKind::Vault(..))
+        {
+            return Self::make_err(ConfigErrorImpl::RemoteOptionError(String::from(
+                "with_cache is not supported for Vault sources, secrets would be stored in plain text",
+            )));
+        }
+
+        self.with_http_option("with_cache", |http| {
+            http.cache = Some(file_path.as_ref().to_path_buf())
+        })
+    }
+
+    // set option of requests of the last declared source, failing unless it's a remote source.
+    #[cfg(feature = "http")]
+    fn with_http_option(
+        mut self,
+        name: &str,
+        option: impl FnOnce(&mut http_impl::HttpOptions),
+    ) -> Self {
+        // check error
+        if self.err.is_some() {
+            return self;
+        }
+
+        match self.declared.last_mut() {
+            Some(declared) if Self::is_remote(&declared.info.kind) => {
+                option(&mut declared.http);
+                self
+            }
+            declared => {
+                let found = declared.map_or(String::from("no source"), |declared| {
+                    declared.info.kind.to_string()
+                });
+                Self::make_err(ConfigErrorImpl::RemoteOptionError(format!(
+                    "{} requires a url, Consul, etcd or Vault source declared right before it, found: {}",
+                    name, found
+                )))
+            }
+        }
+    }
+
+    #[cfg(feature = "http")]
+    fn is_remote(kind: &SourceKind) -> bool {
+        match kind {
+            SourceKind::Url(..) => true,
+            #[cfg(feature = "consul")]
+            SourceKind::Consul(..) => true,
+            #[cfg(feature = "etcd")]
+            SourceKind::Etcd(..) => true,
+            #[cfg(feature = "vault")]
+            SourceKind::Vault(..) => true,
+            _ => false,
+        }
+    }
+
+    pub fn sources(&self) -> Vec<SourceInfo> {
+        self.declared
+            .iter()
+            .map(|declared| declared.info.clone())
+            .collect()
+    }
+
+    /// Read and merge sources once
+    pub fn load(&self) -> Result<LoadedImpl, ConfigErrorImpl> {
+        if let Some(err) = &self.err {
+            return Err(err.clone());
+        }
+
+        Ok(LoadedImpl {
+            values: self.merge()?,
+            normalize_keys: self.normalize_keys,
+        })
     }
 
     /// Build configs into T
-    pub fn build<T>(self) -> Result<T, super::config_error_impl::ConfigErrorImpl>
+    pub fn build<T>(&self) -> Result<T, super::config_error_impl::ConfigErrorImpl>
     where
         T: DeserializeOwned + Debug,
     {
-        if let Some(err) = self.err {
-            return Err(err);
+        if let Some(err) = &self.err {
+            return Err(err.clone());
         }
 
-        let config_vals = if !self.prefix.is_empty() {
-            self.filter_env_prefix()
-        } else {
-            self
-        };
+        let config_vals = self.merge()?;
+
+        let ret = serde_json::from_value::<T>(serde_json::Value::Object(config_vals))?;
 
-        if !&config_vals.overwrite {
-            let is_env_file_env_duplicate = config_vals
-                .env
-                .keys()
-                .any(|k| config_vals.files_env.contains_key(k));
-            if is_env_file_env_duplicate {
-                return Err(ConfigErrorImpl::DuplicateKey(String::from(
-                    "found duplicate key between env and values with json, toml and yaml ",
+        Ok(ret)
+    }
+
+    /// Build configs into T, loading async sources concurrently
+    #[cfg(feature = "async")]
+    pub async fn build_async<T>(&self) -> Result<T, ConfigErrorImpl>
+    where
+        T: DeserializeOwned + Debug,
+    {
+        if let Some(err) = &self.err {
+            return Err(err.clone());
+        }
+
+        let mut config = self.clone();
+        let pending = config
+            .declared
+            .iter_mut()
+            .filter(|declared| declared.async_source.is_some())
+            .collect::<Vec<_>>();
+        let loaded = futures::future::join_all(
+            pending
+                .iter()
+                .filter_map(|declared| declared.async_source.as_ref())
+                .map(|source| source.load()),
+        )
+        .await;
+
+        // loaded values are kept as values of the source, like values set in memory
+        for (declared, value) in pending.into_iter().zip(loaded) {
+            let name = declared.async_source.as_ref().map(|source| source.name());
+            let name = name.unwrap_or_default();
+            let value =
+                value.map_err(|err| ConfigErrorImpl::SourceError(format!("{}: {}", name, err)))?;
+            let serde_json::Value::Object(values) = serde_json::Value::from(value) else {
+                return Err(ConfigErrorImpl::SourceError(format!(
+                    "{}: expected a table of keys",
+                    name
                 )));
+            };
+            declared.values = values;
+            declared.async_source = None;
+        }
+
+        config.build::<T>()
+    }
+
+    // blocking queries of consul sources in parallel, returning the first greater index.
+    #[cfg(feature = "consul")]
+    pub fn watch_consul(
+        &self,
+        index: u64,
+        wait: std::time::Duration,
+    ) -> Result<u64, ConfigErrorImpl> {
+        let watches = self
+            .declared
+            .iter()
+            .filter_map(|declared| match &declared.info.kind {
+                SourceKind::Consul(addr, prefix) => {
+                    let (addr, prefix, options) =
+                        (addr.clone(), prefix.clone(), declared.http.clone());
+                    Some(move || consul_impl::watch(&addr, &prefix, index, wait, &options))
+                }
+                _ => None,
+            })
+            .collect();
+        Self::watch(index, watches, "consul")
+    }
+
+    // watch requests of etcd sources in parallel, returning the first greater revision.
+    #[cfg(feature = "etcd")]
+    pub fn watch_etcd(
+        &self,
+        revision: u64,
+        wait: std::time::Duration,
+    ) -> Result<u64, ConfigErrorImpl> {
+        let watches = self
+            .declared
+            .iter()
+            .filter_map(|declared| match &declared.info.kind {
+                SourceKind::Etcd(addr, prefix) => {
+                    let (addr, prefix, options) =
+                        (addr.clone(), prefix.clone(), declared.http.clone());
+                    Some(move || etcd_impl::watch(&addr, &prefix, revision, wait, &options))
+                }
+                _ => None,
+            })
+            .collect();
+        Self::watch(revision, watches, "etcd")
+    }
+
+    #[cfg(feature = "vault")]
+    pub fn vault_metadata(&self) -> Result<Vec<VaultMetadata>, ConfigErrorImpl> {
+        self.declared
+            .iter()
+            .filter_map(|declared| match &declared.info.kind {
+                SourceKind::Vault(url, _) => Some(
+                    http_impl::get(url, &declared.http)
+                        .and_then(|body| vault_impl::metadata(url, &body)),
+                ),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // run watches in parallel, returning the first index greater than index, or index when none has changed.
+    #[cfg(any(feature = "consul", feature = "etcd"))]
+    fn watch<F>(index: u64, watches: Vec<F>, name: &str) -> Result<u64, ConfigErrorImpl>
+    where
+        F: FnOnce() -> Result<u64, ConfigErrorImpl> + Send + 'static,
+    {
+        if watches.is_empty() {
+            return Err(ConfigErrorImpl::BuildError(format!(
+                "no {} source declared",
+                name
+            )));
+        }
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        for watch in watches {
+            let tx = tx.clone();
+            std::thread::spawn(move || {
+                let _ = tx.send(watch());
+            });
+        }
+        drop(tx);
+
+        for ret in rx {
+            let new_index = ret?;
+            if new_index > index {
+                return Ok(new_index);
             }
         }
+        Ok(index)
+    }
 
-        let config_vals = config_vals.merge_env_files_env();
+    /// Build subtree at dotted key path into T
+    pub fn build_at<T>(&self, key: &str) -> Result<T, ConfigErrorImpl>
+    where
+        T: DeserializeOwned + Debug,
+    {
+        if let Some(err) = &self.err {
+            return Err(err.clone());
+        }
 
-        let ret = serde_json::from_value::<T>(serde_json::Value::Object(config_vals))?;
+        let value = Self::subtree(self.merge()?, key, self.normalize_keys)?;
+
+        let ret = serde_json::from_value::<T>(value)?;
 
         Ok(ret)
     }
 
-    fn is_exist(map: &serde_json::Map<String, serde_json::Value>, key: &str) -> bool {
-        if map.contains_key(key) {
-            return true;
+    // value at dotted key path of merged configs, failing if there is none.
+    fn subtree(
+        values: serde_json::Map<String, serde_json::Value>,
+        key: &str,
+        normalize_keys: bool,
+    ) -> Result<serde_json::Value, ConfigErrorImpl> {
+        let mut value = serde_json::Value::Object(values);
+        for segment in key.split('.') {
+            let segment = match normalize_keys {
+                true => Self::normalize_key(segment),
+                false => segment.to_string(),
+            };
+            value = match value {
+                serde_json::Value::Object(mut v) => v.remove(&segment),
+                _ => None,
+            }
+            .ok_or_else(|| ConfigErrorImpl::BuildError(format!("key path not found: {}", key)))?;
         }
-        false
+        Ok(value)
     }
 
-    fn filter_env_prefix(mut self) -> Self {
-        let filtered_env = self
-            .env
-            .into_iter()
-            .filter(|(key, _)| key.starts_with(self.prefix))
-            .collect::<serde_json::Map<String, serde_json::Value>>();
-        self.env = filtered_env;
+    pub fn build_validated<T>(&self) -> Result<T, ConfigErrorImpl>
+    where
+        T: DeserializeOwned + Debug + Validate,
+    {
+        let ret = self.build::<T>()?;
+
+        let mut validator = Validator::new();
+        ret.validate(&mut validator);
+        if !validator.errors().is_empty() {
+            return Err(ConfigErrorImpl::ValidationError(validator.into_errors()));
+        }
+
+        Ok(ret)
+    }
+
+    /// Build configs into T, validated against the schema of T
+    #[cfg(feature = "schema")]
+    pub fn build_with_schema<T>(&self) -> Result<T, ConfigErrorImpl>
+    where
+        T: DeserializeOwned + Debug + schemars::JsonSchema,
+    {
+        let mut config = self.clone();
+        config
+            .schemas
+            .insert(0, Schema::Value(schema_impl::generate::<T>()));
+        config.build::<T>()
+    }
+
+    // env var from the injected env vars, last declared first, then from the process environment.
+    #[cfg(feature = "encryption")]
+    fn env_var(&self, name: &str) -> Option<String> {
+        self.declared
+            .iter()
+            .rev()
+            .filter(|declared| matches!(declared.info.kind, SourceKind::EnvVars))
+            .find_map(|declared| declared.values.get(name))
+            .and_then(|value| value.as_str().map(str::to_string))
+            .or_else(|| env::var(name).ok())
+    }
+
+    // declare source in its default layer, with conflict policy for next declared sources.
+    fn declare(
+        mut self,
+        kind: SourceKind,
+        layer: Layer,
+        values: serde_json::Map<String, serde_json::Value>,
+    ) -> Self {
+        self.declared.push(Declared {
+            info: SourceInfo {
+                kind,
+                layer,
+                conflict: self.conflict,
+            },
+            values,
+            #[cfg(feature = "async")]
+            async_source: None,
+            #[cfg(feature = "http")]
+            http: http_impl::HttpOptions::default(),
+        });
         self
     }
 
-    fn merge_env_files_env(mut self) -> serde_json::Map<String, serde_json::Value> {
-        self.env.extend(self.files_env.into_iter());
-        self.env
+    fn declare_file(self, kind: SourceKind) -> Self {
+        // check error
+        if self.err.is_some() {
+            return self;
+        }
+
+        let layer = match kind {
+            SourceKind::EnvFile(_) => Layer::Env,
+            _ => Layer::File,
+        };
+        self.declare(kind, layer, serde_json::Map::new())
+    }
+
+    // read declared sources in declaration order, e.g. snapshot env vars and read files.
+    fn read(&self) -> Result<Vec<Source>, ConfigErrorImpl> {
+        let mut sources = Vec::new();
+        for (origin, declared) in self.declared.iter().enumerate() {
+            let source = |prefixed: bool, env: bool, entries: Vec<Entry>| Source {
+                #[cfg(feature = "schema")]
+                kind: declared.info.kind.clone(),
+                layer: declared.info.layer,
+                conflict: declared.info.conflict,
+                origin,
+                prefixed,
+                env,
+                entries,
+                spellings: Vec::new(),
+            };
+            match &declared.info.kind {
+                SourceKind::OsEnv => {
+                    let vars = env::vars()
+                        .map(|(key, val)| (key, serde_json::Value::String(val)))
+                        .collect();
+                    sources.push(source(true, true, Self::top_level(vars)));
+                }
+                SourceKind::EnvVars => {
+                    sources.push(source(true, true, Self::top_level(declared.values.clone())));
+                }
+                SourceKind::Value(_) => {
+                    sources.push(source(
+                        true,
+                        false,
+                        Self::top_level(declared.values.clone()),
+                    ));
+                }
+                SourceKind::EnvFile(file_path) => {
+                    let vars = env_file_reader::read_file(file_path)
+                        .map_err(|err| Self::make_err_open_file(file_path, err))?
+                        .into_iter()
+                        .map(|(key, val)| (key, serde_json::Value::String(val)))
+                        .collect();
+                    sources.push(source(true, true, Self::top_level(vars)));
+                }
+                SourceKind::Json(file_path) => {
+                    let file = std::fs::File::open(file_path)
+                        .map_err(|err| ConfigErrorImpl::FileError(err.to_string()))?;
+                    let reader = std::io::BufReader::new(file);
+                    let val = serde_json::from_reader::<_, serde_json::Value>(reader)
+                        .map_err(|err| ConfigErrorImpl::JsonError(err.to_string()))?;
+                    sources.extend(Self::file_entries(val).map(|e| source(false, false, e)));
+                }
+                SourceKind::Json5(file_path) => {
+                    let entries =
+                        Self::parse(Format::Json5, &Self::load_file_to_string(file_path)?)?;
+                    sources.extend(entries.into_iter().map(|e| source(false, false, e)));
+                }
+                SourceKind::Ron(file_path) => {
+                    let entries = Self::parse(Format::Ron, &Self::load_file_to_string(file_path)?)?;
+                    sources.extend(entries.into_iter().map(|e| source(false, false, e)));
+                }
+                SourceKind::Toml(file_path) => {
+                    let entries =
+                        Self::parse(Format::Toml, &Self::load_file_to_string(file_path)?)?;
+                    sources.extend(entries.into_iter().map(|e| source(false, false, e)));
+                }
+                SourceKind::Yaml(file_path) => {
+                    let entries =
+                        Self::parse(Format::Yaml, &Self::load_file_to_string(file_path)?)?;
+                    sources.extend(entries.into_iter().map(|e| source(false, false, e)));
+                }
+                #[cfg(feature = "http")]
+                SourceKind::Url(url, format) => {
+                    let entries = Self::parse(*format, &http_impl::get(url, &declared.http)?)?;
+                    sources.extend(entries.into_iter().map(|e| source(false, false, e)));
+                }
+                #[cfg(feature = "consul")]
+                SourceKind::Consul(addr, prefix) => {
+                    let body =
+                        http_impl::fetch(&consul_impl::url(addr, prefix), None, &declared.http)?;
+                    let entries = consul_impl::values(body.as_deref(), prefix)?
+                        .into_iter()
+                        .map(|(path, v)| (path, Some(Self::parse_str(&v))))
+                        .collect();
+                    sources.push(source(false, false, entries));
+                }
+                #[cfg(feature = "etcd")]
+                SourceKind::Etcd(addr, prefix) => {
+                    let body = http_impl::post(
+                        &etcd_impl::range_url(addr),
+                        &etcd_impl::range_body(prefix),
+                        &declared.http,
+                    )?;
+                    let entries = etcd_impl::values(&body, prefix)?
+                        .into_iter()
+                        .map(|(path, v)| (path, Some(Self::parse_str(&v))))
+                        .collect();
+                    sources.push(source(false, false, entries));
+                }
+                #[cfg(feature = "vault")]
+                SourceKind::Vault(url, key) => {
+                    // fields as they are, so keys with `.` aren't split into tables
+                    let data = vault_impl::data(&http_impl::get(url, &declared.http)?)?;
+                    let mut entries = Self::top_level(data);
+                    if let Some(key) = key {
+                        let key: Vec<String> = key.split('.').map(String::from).collect();
+                        for (path, _) in &mut entries {
+                            path.splice(0..0, key.iter().cloned());
+                        }
+                    }
+                    sources.push(source(false, false, entries));
+                }
+                #[cfg(feature = "sqlite")]
+                SourceKind::Sqlite(file_path, query) => {
+                    let entries = sqlite_impl::values(file_path, query, Self::parse_str)?
+                        .into_iter()
+                        .map(|(path, v)| (path, Some(v)))
+                        .collect();
+                    sources.push(source(false, false, entries));
+                }
+                #[cfg(feature = "sops")]
+                SourceKind::Sops(file_path, format) => {
+                    let content = Self::load_file_to_string(file_path)?;
+                    // yaml value keeps the order of keys of json too
+                    let document =
+                        serde_yaml::from_str::<serde_yaml::Value>(&content).map_err(|err| {
+                            match format {
+                                Format::Json => ConfigErrorImpl::JsonError(err.to_string()),
+                                _ => ConfigErrorImpl::YamlError(err.to_string()),
+                            }
+                        })?;
+                    let identity_file =
+                        sops_impl::identity_file(self.age_identity.as_deref(), |name| {
+                            self.env_var(name)
+                        })?;
+                    let val = sops_impl::decrypt(document, &identity_file)?;
+                    sources.extend(Self::file_entries(val).map(|e| source(false, false, e)));
+                }
+                SourceKind::Unset(key) => {
+                    let path = key.split('.').map(String::from).collect();
+                    sources.push(source(false, false, vec![(path, None)]));
+                }
+                // nested key paths, so it only overwrites the nested values it sets
+                SourceKind::Args => {
+                    let mut values = declared.values.clone();
+                    for key in &self.args_lists {
+                        Self::wrap_array(&mut values, key);
+                    }
+                    sources.push(source(false, false, Self::nested(values)));
+                }
+                #[cfg(feature = "clap")]
+                SourceKind::Clap => {
+                    sources.push(source(false, false, Self::nested(declared.values.clone())));
+                }
+                #[cfg(feature = "async")]
+                SourceKind::Async(name) => {
+                    if declared.async_source.is_some() {
+                        return Err(ConfigErrorImpl::BuildError(format!(
+                            "async source {} requires build_async",
+                            name
+                        )));
+                    }
+                    sources.push(source(
+                        false,
+                        false,
+                        Self::top_level(declared.values.clone()),
+                    ));
+                }
+            }
+        }
+        Ok(sources)
+    }
+
+    // parse document in format into entries of sources, each yaml document is layered in order like separate sources.
+    fn parse(format: Format, s: &str) -> Result<Vec<Vec<Entry>>, ConfigErrorImpl> {
+        let val = match format {
+            Format::Json => serde_json::from_str::<serde_json::Value>(s)
+                .map_err(|err| ConfigErrorImpl::JsonError(err.to_string()))?,
+            Format::Json5 => {
+                json5::from_str::<serde_json::Value>(s).map_err(Self::make_err_json5)?
+            }
+            Format::Ron => {
+                let RonValue::Value(val) = ron::from_str::<RonValue>(s)
+                    .map_err(|err| ConfigErrorImpl::RonError(err.to_string()))?;
+                val
+            }
+            Format::Toml => {
+                let val = toml::from_str::<toml::Value>(s)
+                    .map_err(|err| ConfigErrorImpl::TomlError(err.to_string()))?;
+                Self::toml_to_json(val)
+            }
+            Format::Yaml => {
+                let mut ret = Vec::new();
+                for document in serde_yaml::Deserializer::from_str(s) {
+                    let (val, unset) = Self::parse_yaml_document(document)?;
+                    ret.extend(Self::file_entries(val));
+                    if !unset.is_empty() {
+                        ret.push(unset.into_iter().map(|path| (path, None)).collect());
+                    }
+                }
+                return Ok(ret);
+            }
+        };
+        Ok(Self::file_entries(val).into_iter().collect())
+    }
+
+    // entries of top level keys of values.
+    fn top_level(values: serde_json::Map<String, serde_json::Value>) -> Vec<Entry> {
+        values
+            .into_iter()
+            .map(|(k, v)| (vec![k], Some(v)))
+            .collect()
+    }
+
+    // entries of parsed file, ignoring non-table root.
+    fn file_entries(value: serde_json::Value) -> Option<Vec<Entry>> {
+        match value {
+            serde_json::Value::Object(v) => Some(Self::top_level(v)),
+            _ => None,
+        }
+    }
+
+    // entries of nested key paths of values.
+    fn nested(values: serde_json::Map<String, serde_json::Value>) -> Vec<Entry> {
+        let mut entries = Vec::new();
+        Self::flatten(&mut Vec::new(), values, &mut entries);
+        entries
+    }
+
+    fn flatten(
+        path: &mut Vec<String>,
+        values: serde_json::Map<String, serde_json::Value>,
+        entries: &mut Vec<Entry>,
+    ) {
+        for (key, val) in values {
+            path.push(key);
+            match val {
+                serde_json::Value::Object(v) if !v.is_empty() => Self::flatten(path, v, entries),
+                val => entries.push((path.clone(), Some(val))),
+            }
+            path.pop();
+        }
+    }
+
+    // merge sources layer by layer, higher layer overwrites the key paths it sets in lower layers.
+    fn merge(&self) -> Result<serde_json::Map<String, serde_json::Value>, ConfigErrorImpl> {
+        let prefix = self.prefix;
+        let mut sources = self.read()?;
+        for source in sources.iter_mut().filter(|source| source.env) {
+            source.entries = self.parse_env(std::mem::take(&mut source.entries))?;
+        }
+        if !prefix.is_empty() {
+            for source in sources.iter_mut().filter(|source| source.prefixed) {
+                source
+                    .entries
+                    .retain(|(path, _)| path[0].starts_with(prefix));
+            }
+        }
+
+        if self.normalize_keys {
+            for source in sources.iter_mut() {
+                let prefix = if source.prefixed { prefix } else { "" };
+                (source.entries, source.spellings) =
+                    self.normalize_entries(std::mem::take(&mut source.entries), prefix)?;
+            }
+        }
+
+        // stable, keeps declaration order inside a layer
+        sources.sort_by_key(|source| source.layer);
+
+        let mut merged = serde_json::Map::new();
+        for layer in sources.chunk_by(|a, b| a.layer == b.layer) {
+            for (path, value) in self.resolve_layer(layer)? {
+                match value {
+                    Some(value) => self.set_path(&mut merged, &path, 0, value),
+                    None => Self::unset_path(&mut merged, &path),
+                }
+            }
+        }
+
+        #[cfg(feature = "encryption")]
+        if let Some(key) = &self.decryption_key {
+            let key = match key {
+                DecryptionKey::File(file_path) => Self::load_file_to_string(file_path)?,
+                DecryptionKey::Env(name) => self.env_var(name).ok_or_else(|| {
+                    ConfigErrorImpl::DecryptionError(
+                        String::new(),
+                        format!("key env var {} not found", name),
+                    )
+                })?,
+            };
+            let key = encryption_impl::parse_key(&key)
+                .map_err(|err| ConfigErrorImpl::DecryptionError(String::new(), err))?;
+            for (k, v) in merged.iter_mut() {
+                encryption_impl::decrypt_all(&key, v, &mut vec![k.clone()])?;
+            }
+        }
+
+        #[cfg(feature = "schema")]
+        if !self.schemas.is_empty() {
+            self.check_schemas(&serde_json::Value::Object(merged.clone()), &sources)?;
+        }
+
+        Ok(merged)
+    }
+
+    // validate merged configs against every schema, failing with all violations and the source of their values.
+    #[cfg(feature = "schema")]
+    fn check_schemas(
+        &self,
+        merged: &serde_json::Value,
+        sources: &[Source],
+    ) -> Result<(), ConfigErrorImpl> {
+        let mut violations = Vec::new();
+        for schema in &self.schemas {
+            let schema = match schema {
+                Schema::Value(schema) => schema.clone(),
+                Schema::File(file_path) => {
+                    serde_json::from_str(&Self::load_file_to_string(file_path)?)
+                        .map_err(|err| ConfigErrorImpl::JsonError(err.to_string()))?
+                }
+            };
+            for (pointer, path, message) in schema_impl::violations(&schema, merged)? {
+                violations.push(SchemaViolation {
+                    source: Self::source_of(merged, sources, &path),
+                    pointer,
+                    message,
+                });
+            }
+        }
+        if !violations.is_empty() {
+            return Err(ConfigErrorImpl::SchemaError(violations));
+        }
+        Ok(())
+    }
+
+    // source supplying the merged value at key path, the highest precedence one with the same value,
+    // else with any value at or under the key path, e.g. a table merged from several sources.
+    #[cfg(feature = "schema")]
+    fn source_of(
+        merged: &serde_json::Value,
+        sources: &[Source],
+        path: &[String],
+    ) -> Option<SourceKind> {
+        let expected = schema_impl::get(merged, path);
+        let mut found = None;
+        // sorted by layer in declaration order, the last one has the highest precedence
+        for source in sources.iter().rev() {
+            for (entry_path, value) in source.entries.iter().rev() {
+                let Some(value) = value else {
+                    continue;
+                };
+                let value = match path.strip_prefix(entry_path.as_slice()) {
+                    Some(rest) => match schema_impl::get(value, rest) {
+                        Some(value) => Some(value),
+                        None => continue,
+                    },
+                    None if entry_path.starts_with(path) => None,
+                    None => continue,
+                };
+                if value.is_some() && value == expected {
+                    return Some(source.kind.clone());
+                }
+                found.get_or_insert_with(|| source.kind.clone());
+            }
+        }
+        found
+    }
+
+    // parse raw env var values, into arrays or maps for names set with `with_env_list`, `with_env_map` and `with_env_indexed`,
+    // and into scalars for others. Items of lists and maps are kept as strings, and indexed items are parsed as scalars.
+    fn parse_env(&self, entries: Vec<Entry>) -> Result<Vec<Entry>, ConfigErrorImpl> {
+        let separator = self.env_separator.unwrap_or(',');
+        let mut indexed: BTreeMap<String, Vec<(usize, serde_json::Value)>> = BTreeMap::new();
+        let mut ret = Vec::new();
+        for (path, value) in entries {
+            let Some(serde_json::Value::String(raw)) = value else {
+                ret.push((path, value));
+                continue;
+            };
+            let key = &path[0];
+            if let Some((name, index)) = key.rsplit_once('_')
+                && let Ok(index) = index.parse::<usize>()
+                && self.env_parse.get(name) == Some(&EnvParse::Indexed)
+            {
+                indexed
+                    .entry(name.to_string())
+                    .or_default()
+                    .push((index, Self::parse_str(&raw)));
+                continue;
+            }
+            let value = match self.env_parse.get(key) {
+                Some(EnvParse::List) => serde_json::Value::Array(
+                    Self::split_env(key, &raw, separator)?
+                        .into_iter()
+                        .map(|item| serde_json::Value::String(Self::unquote(item)))
+                        .collect(),
+                ),
+                Some(EnvParse::Map) => serde_json::Value::Object(
+                    Self::split_env(key, &raw, separator)?
+                        .into_iter()
+                        .map(|item| match Self::split_env(key, item, '=')?.first() {
+                            // the first part is followed by the first `=` outside quotes
+                            Some(k) if k.len() < item.len() => Ok((
+                                Self::unquote(k),
+                                serde_json::Value::String(Self::unquote(&item[k.len() + 1..])),
+                            )),
+                            _ => Err(ConfigErrorImpl::EnvError(format!(
+                                "expected `key=value` in {}, found: {}",
+                                key,
+                                Self::unquote(item)
+                            ))),
+                        })
+                        .collect::<Result<_, _>>()?,
+                ),
+                _ => Self::parse_str(&raw),
+            };
+            ret.push((path, Some(value)));
+        }
+
+        for (name, mut items) in indexed {
+            if ret.iter().any(|(path, _)| path[0] == name) {
+                return Err(ConfigErrorImpl::DuplicateKey(name));
+            }
+            items.sort_by_key(|(index, _)| *index);
+            // indexes start at 0 without gaps or duplicates
+            for (i, (index, _)) in items.iter().enumerate() {
+                if *index < i {
+                    return Err(ConfigErrorImpl::EnvError(format!(
+                        "duplicated index {} of {}",
+                        index, name
+                    )));
+                }
+                if *index > i {
+                    return Err(ConfigErrorImpl::EnvError(format!(
+                        "missing index {} of {}, found: {}_{}",
+                        i, name, name, index
+                    )));
+                }
+            }
+            let items = items.into_iter().map(|(_, v)| v).collect();
+            ret.push((vec![name], Some(serde_json::Value::Array(items))));
+        }
+
+        Ok(ret)
+    }
+
+    // split env var value by separator, except inside `"` or `'` quotes. Items are returned as they are, with their quotes,
+    // and an empty item after a trailing separator is dropped.
+    fn split_env<'a>(
+        key: &str,
+        raw: &'a str,
+        separator: char,
+    ) -> Result<Vec<&'a str>, ConfigErrorImpl> {
+        let mut items = Vec::new();
+        if raw.trim().is_empty() {
+            return Ok(items);
+        }
+
+        let mut start = 0;
+        let mut quote = None;
+        for (i, c) in raw.char_indices() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if c == separator => {
+                    items.push(&raw[start..i]);
+                    start = i + c.len_utf8();
+                }
+                None if c == '"' || c == '\'' => quote = Some(c),
+                None => {}
+            }
+        }
+        if quote.is_some() {
+            return Err(ConfigErrorImpl::EnvError(format!(
+                "unterminated quote in {}",
+                key
+            )));
+        }
+        let last = &raw[start..];
+        if items.is_empty() || !last.trim().is_empty() {
+            items.push(last);
+        }
+
+        Ok(items)
+    }
+
+    // remove quotes of a split env var item, whitespaces outside quotes are trimmed.
+    fn unquote(item: &str) -> String {
+        let mut ret = String::new();
+        let mut spaces = String::new(); // whitespaces outside quotes, kept only when followed by other chars
+        let mut quote = None;
+        for c in item.chars() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => ret.push(c),
+                None if c.is_whitespace() => {
+                    if !ret.is_empty() {
+                        spaces.push(c);
+                    }
+                }
+                None => {
+                    ret.push_str(&std::mem::take(&mut spaces));
+                    match c {
+                        '"' | '\'' => quote = Some(c),
+                        _ => ret.push(c),
+                    }
+                }
+            }
+        }
+
+        ret
+    }
+
+    // resolve key paths set by more than one source of the same layer, by the conflict policy of the later declared source.
+    // Arrays at the same key path are combined instead, unless their array merge strategy is `Replace`.
+    // Later documents of a yaml file overwrite earlier ones, like `Conflict::LastWins`.
+    fn resolve_layer(&self, sources: &[Source]) -> Result<Vec<Entry>, ConfigErrorImpl> {
+        // entries with their original spelling and origin
+        let mut entries: Vec<(Vec<String>, Option<serde_json::Value>, String, usize)> = Vec::new();
+        for source in sources {
+            for (i, (path, value)) in source.entries.iter().enumerate() {
+                let spelling = match source.spellings.get(i) {
+                    Some(spelling) => spelling.clone(),
+                    None => path.join("."),
+                };
+                // entries are never related to each other, so an entry at the same key path is the only conflicting one
+                if let Some((_, existing, _, origin)) =
+                    entries.iter_mut().find(|(p, _, _, _)| p == path)
+                    && (source.conflict == Conflict::LastWins
+                        || *origin == source.origin
+                        || (matches!((&existing, value), (Some(e), Some(v)) if e.is_array() && v.is_array())
+                            && self.array_merge_of(path) != ArrayMerge::Replace))
+                {
+                    *existing = match (existing.take(), value) {
+                        (Some(previous), Some(value)) => {
+                            Some(self.merge_value(path, previous, value.clone()))
+                        }
+                        (_, value) => value.clone(),
+                    };
+                    continue;
+                }
+                let is_conflict = |p: &Vec<String>| p.starts_with(path) || path.starts_with(p);
+                if let Some((p, _, s, _)) = entries
+                    .iter()
+                    .find(|(p, _, _, origin)| is_conflict(p) && *origin != source.origin)
+                {
+                    match source.conflict {
+                        Conflict::Error => {
+                            return Err(Self::make_err_duplicate(
+                                path,
+                                (&p.join("."), s),
+                                (&path.join("."), &spelling),
+                            ));
+                        }
+                        Conflict::FirstWins => continue,
+                        Conflict::LastWins => {}
+                    }
+                }
+                entries.retain(|(p, _, _, _)| !is_conflict(p));
+                entries.push((path.clone(), value.clone(), spelling, source.origin));
+            }
+        }
+        Ok(entries
+            .into_iter()
+            .map(|(path, value, _, _)| (path, value))
+            .collect())
+    }
+
+    // normalize keys of source entries, and nested keys of their values, into snake_case, except keys inside raw key paths.
+    // Prefix is stripped from top level keys first, e.g. `APP_DATABASE_URL` into `database_url`.
+    // Returns the original spelling of each entry, and fails on keys colliding after normalization.
+    fn normalize_entries(
+        &self,
+        entries: Vec<Entry>,
+        prefix: &str,
+    ) -> Result<(Vec<Entry>, Vec<String>), ConfigErrorImpl> {
+        let mut seen: HashMap<Vec<String>, String> = HashMap::new();
+        let mut ret = Vec::new();
+        let mut spellings = Vec::new();
+        for (path, value) in entries {
+            let spelling = path.join(".");
+            let mut normalized_path: Vec<String> = Vec::new();
+            for (i, key) in path.iter().enumerate() {
+                let key = match i {
+                    0 => key.strip_prefix(prefix).unwrap_or(key),
+                    _ => key,
+                };
+                let key = match self.is_raw_keys(&normalized_path) {
+                    true => key.to_string(),
+                    false => Self::normalize_key(key),
+                };
+                normalized_path.push(key);
+            }
+            let path = normalized_path;
+            if let Some(other) = seen.insert(path.clone(), spelling.clone()) {
+                let normalized = path.join(".");
+                return Err(Self::make_err_duplicate(
+                    &path,
+                    (&normalized, &other),
+                    (&normalized, &spelling),
+                ));
+            }
+            let value = value
+                .map(|value| self.normalize_value(&path, value))
+                .transpose()?;
+            ret.push((path, value));
+            spellings.push(spelling);
+        }
+        Ok((ret, spellings))
+    }
+
+    fn normalize_value(
+        &self,
+        path: &[String],
+        value: serde_json::Value,
+    ) -> Result<serde_json::Value, ConfigErrorImpl> {
+        if self.is_raw_keys(path) {
+            return Ok(value);
+        }
+        match value {
+            serde_json::Value::Object(values) => {
+                let mut spellings: HashMap<String, String> = HashMap::new();
+                let mut ret = serde_json::Map::new();
+                for (key, value) in values {
+                    let normalized = Self::normalize_key(&key);
+                    let path = [path, std::slice::from_ref(&normalized)].concat();
+                    if let Some(other) = spellings.insert(normalized.clone(), key.clone()) {
+                        let normalized = path.join(".");
+                        let other = [&path[..path.len() - 1], &[other]].concat().join(".");
+                        let key = [&path[..path.len() - 1], &[key]].concat().join(".");
+                        return Err(Self::make_err_duplicate(
+                            &path,
+                            (&normalized, &other),
+                            (&normalized, &key),
+                        ));
+                    }
+                    ret.insert(normalized, self.normalize_value(&path, value)?);
+                }
+                Ok(serde_json::Value::Object(ret))
+            }
+            serde_json::Value::Array(values) => Ok(serde_json::Value::Array(
+                values
+                    .into_iter()
+                    .map(|value| self.normalize_value(path, value))
+                    .collect::<Result<_, _>>()?,
+            )),
+            value => Ok(value),
+        }
+    }
+
+    // keys inside the table at key path are kept as-is, e.g. map fields of labels or headers.
+    fn is_raw_keys(&self, path: &[String]) -> bool {
+        self.raw_keys.iter().any(|raw| raw == path)
+    }
+
+    // normalize key into snake_case, e.g. `DATABASE_URL`, `database-url` and `databaseUrl` into `database_url`.
+    fn normalize_key(key: &str) -> String {
+        let chars: Vec<char> = key.chars().collect();
+        let mut ret = String::new();
+        for (i, &c) in chars.iter().enumerate() {
+            let is_separator = c == '-' || c == '_';
+            // word boundary of camelCase, e.g. `databaseUrl`, `ipv4Addr` and `HTTPServer`
+            let is_boundary = c.is_uppercase()
+                && i > 0
+                && (chars[i - 1].is_lowercase()
+                    || chars[i - 1].is_ascii_digit()
+                    || (chars[i - 1].is_uppercase()
+                        && chars.get(i + 1).is_some_and(|next| next.is_lowercase())));
+            if (is_separator || is_boundary) && !ret.is_empty() && !ret.ends_with('_') {
+                ret.push('_');
+            }
+            if !is_separator {
+                ret.extend(c.to_lowercase());
+            }
+        }
+        ret.trim_end_matches('_').to_string()
+    }
+
+    // set value at key path from depth, replacing non-table values on the way with tables.
+    // The whole key path is kept for the array merge strategies of nested keys.
+    fn set_path(
+        &self,
+        map: &mut serde_json::Map<String, serde_json::Value>,
+        path: &[String],
+        depth: usize,
+        value: serde_json::Value,
+    ) {
+        match &path[depth..] {
+            [] => {}
+            [last] => {
+                let value = match map.remove(last) {
+                    Some(previous) => self.merge_value(path, previous, value),
+                    None => value,
+                };
+                map.insert(last.clone(), value);
+            }
+            [segment, ..] => {
+                let entry = map
+                    .entry(segment.as_str())
+                    .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+                if !entry.is_object() {
+                    *entry = serde_json::Value::Object(serde_json::Map::new());
+                }
+                if let serde_json::Value::Object(v) = entry {
+                    self.set_path(v, path, depth + 1, value);
+                }
+            }
+        }
+    }
+
+    // remove value at key path, if any.
+    fn unset_path(map: &mut serde_json::Map<String, serde_json::Value>, path: &[String]) {
+        match path {
+            [] => {}
+            [last] => {
+                map.remove(last);
+            }
+            [segment, rest @ ..] => {
+                if let Some(serde_json::Value::Object(v)) = map.get_mut(segment) {
+                    Self::unset_path(v, rest);
+                }
+            }
+        }
+    }
+
+    // merge value set at key path with the previous value at the same path.
+    // Tables are replaced, but arrays inside are combined with previous arrays at the same key paths by their array merge strategy.
+    fn merge_value(
+        &self,
+        path: &[String],
+        previous: serde_json::Value,
+        value: serde_json::Value,
+    ) -> serde_json::Value {
+        match (previous, value) {
+            (serde_json::Value::Array(previous), serde_json::Value::Array(value)) => {
+                serde_json::Value::Array(Self::merge_array(
+                    self.array_merge_of(path),
+                    previous,
+                    value,
+                ))
+            }
+            (serde_json::Value::Object(mut previous), serde_json::Value::Object(value)) => {
+                serde_json::Value::Object(
+                    value
+                        .into_iter()
+                        .map(|(k, v)| {
+                            let v = match previous.remove(&k) {
+                                Some(p) => self.merge_value(
+                                    &[path, std::slice::from_ref(&k)].concat(),
+                                    p,
+                                    v,
+                                ),
+                                None => v,
+                            };
+                            (k, v)
+                        })
+                        .collect(),
+                )
+            }
+            (_, value) => value,
+        }
+    }
+
+    fn merge_array(
+        array_merge: ArrayMerge,
+        mut previous: Vec<serde_json::Value>,
+        value: Vec<serde_json::Value>,
+    ) -> Vec<serde_json::Value> {
+        match array_merge {
+            ArrayMerge::Replace => return value,
+            ArrayMerge::Append => previous.extend(value),
+            ArrayMerge::Prepend => {
+                let mut value = value;
+                value.extend(previous);
+                return value;
+            }
+            ArrayMerge::Union => {
+                for v in value {
+                    if !previous.contains(&v) {
+                        previous.push(v);
+                    }
+                }
+            }
+            ArrayMerge::ByIndex => {
+                for (i, v) in value.into_iter().enumerate() {
+                    match previous.get_mut(i) {
+                        Some(p) => *p = Self::merge_table(p.take(), v),
+                        None => previous.push(v),
+                    }
+                }
+            }
+            ArrayMerge::ById(field) => {
+                for v in value {
+                    let position = v.get(field).and_then(|id| {
+                        previous
+                            .iter()
+                            .position(|p| p.is_object() && p.get(field) == Some(id))
+                    });
+                    match position {
+                        Some(i) => previous[i] = Self::merge_table(previous[i].take(), v),
+                        None => previous.push(v),
+                    }
+                }
+            }
+        }
+        previous
+    }
+
+    // merge tables key by key, keeping keys only in previous table. Other values are replaced.
+    fn merge_table(previous: serde_json::Value, value: serde_json::Value) -> serde_json::Value {
+        match (previous, value) {
+            (serde_json::Value::Object(mut previous), serde_json::Value::Object(value)) => {
+                for (k, v) in value {
+                    let v = match previous.remove(&k) {
+                        Some(p) => Self::merge_table(p, v),
+                        None => v,
+                    };
+                    previous.insert(k, v);
+                }
+                serde_json::Value::Object(previous)
+            }
+            (_, value) => value,
+        }
+    }
+
+    fn array_merge_of(&self, path: &[String]) -> ArrayMerge {
+        let key = path.join(".");
+        match self.normalize_keys {
+            false => self.array_merge_keys.get(&key).copied(),
+            true => self
+                .array_merge_keys
+                .iter()
+                .find(|(k, _)| k.split('.').map(Self::normalize_key).collect::<Vec<_>>() == path)
+                .map(|(_, array_merge)| *array_merge),
+        }
+        .unwrap_or(self.array_merge)
+    }
+
+    // parse yaml document into its value and the top level keys tagged `!unset`.
+    fn parse_yaml_document(
+        document: serde_yaml::Deserializer,
+    ) -> Result<(serde_json::Value, Vec<Vec<String>>), ConfigErrorImpl> {
+        let mut val = serde_yaml::Value::deserialize(document)
+            .map_err(|err| ConfigErrorImpl::YamlError(err.to_string()))?;
+        Self::apply_yaml_merge(&mut val)?;
+        let unset = match &val {
+            serde_yaml::Value::Mapping(mapping) => mapping
+                .iter()
+                .filter(|(_, v)| Self::is_yaml_unset(v))
+                .filter_map(|(k, _)| k.as_str().map(|k| vec![k.to_string()]))
+                .collect(),
+            _ => Vec::new(),
+        };
+        Self::remove_yaml_unset(&mut val);
+        let val = serde_yaml::from_value::<serde_json::Value>(val)
+            .map_err(|err| ConfigErrorImpl::YamlError(err.to_string()))?;
+        Ok((val, unset))
+    }
+
+    fn is_yaml_unset(val: &serde_yaml::Value) -> bool {
+        matches!(val, serde_yaml::Value::Tagged(tagged) if tagged.tag == "unset")
+    }
+
+    // remove values tagged `!unset`. Nested ones only need removing, as tables set by a source replace the tables of lower layers.
+    fn remove_yaml_unset(val: &mut serde_yaml::Value) {
+        match val {
+            serde_yaml::Value::Mapping(mapping) => {
+                mapping.retain(|_, v| !Self::is_yaml_unset(v));
+                mapping.values_mut().for_each(Self::remove_yaml_unset);
+            }
+            serde_yaml::Value::Sequence(sequence) => {
+                sequence.retain(|v| !Self::is_yaml_unset(v));
+                sequence.iter_mut().for_each(Self::remove_yaml_unset);
+            }
+            serde_yaml::Value::Tagged(tagged) => Self::remove_yaml_unset(&mut tagged.value),
+            _ => {}
+        }
+    }
+
+    // resolve `<<` merge keys, innermost first so that merged mappings carrying their own `<<` are chained.
+    // Keys explicitly set in a mapping win over merged ones, and earlier mappings in a merge sequence win over later ones.
+    fn apply_yaml_merge(val: &mut serde_yaml::Value) -> Result<(), ConfigErrorImpl> {
+        match val {
+            serde_yaml::Value::Mapping(mapping) => {
+                for v in mapping.values_mut() {
+                    Self::apply_yaml_merge(v)?;
+                }
+                let merges = match mapping.remove("<<") {
+                    None => return Ok(()),
+                    Some(serde_yaml::Value::Mapping(merge)) => vec![merge],
+                    Some(serde_yaml::Value::Sequence(sequence)) => sequence
+                        .into_iter()
+                        .map(|v| match v {
+                            serde_yaml::Value::Mapping(merge) => Ok(merge),
+                            _ => Err(ConfigErrorImpl::YamlError(String::from(
+                                "expected a mapping in merge key sequence",
+                            ))),
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                    Some(_) => {
+                        return Err(ConfigErrorImpl::YamlError(String::from(
+                            "expected a mapping or sequence of mappings in merge key",
+                        )));
+                    }
+                };
+                for merge in merges {
+                    for (k, v) in merge {
+                        mapping.entry(k).or_insert(v);
+                    }
+                }
+                Ok(())
+            }
+            serde_yaml::Value::Sequence(sequence) => {
+                sequence.iter_mut().try_for_each(Self::apply_yaml_merge)
+            }
+            serde_yaml::Value::Tagged(tagged) => Self::apply_yaml_merge(&mut tagged.value),
+            _ => Ok(()),
+        }
+    }
+
+    // convert toml value into json value.
+    // Datetimes are kept in their TOML/RFC 3339 text form, e.g. `1979-05-27T07:32:00-08:00`, `1979-05-27` or `07:32:00`,
+    // so they deserialize into strings as well as datetime types parsing from strings(chrono, time, etc).
+    fn toml_to_json(value: toml::Value) -> serde_json::Value {
+        match value {
+            toml::Value::String(v) => serde_json::Value::String(v),
+            toml::Value::Integer(v) => json!(v),
+            toml::Value::Float(v) => json!(v),
+            toml::Value::Boolean(v) => json!(v),
+            toml::Value::Datetime(v) => serde_json::Value::String(v.to_string()),
+            toml::Value::Array(v) => {
+                serde_json::Value::Array(v.into_iter().map(Self::toml_to_json).collect())
+            }
+            toml::Value::Table(v) => serde_json::Value::Object(
+                v.into_iter()
+                    .map(|(k, v)| (k, Self::toml_to_json(v)))
+                    .collect(),
+            ),
+        }
+    }
+
+    // insert only arguments explicitly supplied on command-line, so clap defaults don't shadow other sources.
+    // Arguments of subcommand are nested under the subcommand name.
+    #[cfg(feature = "clap")]
+    fn insert_arg_matches(
+        map: &mut serde_json::Map<String, serde_json::Value>,
+        prefix: &str,
+        command: &clap::Command,
+        matches: &clap::ArgMatches,
+    ) -> Result<(), ConfigErrorImpl> {
+        for arg in command.get_arguments() {
+            let id = arg.get_id().as_str();
+            if matches.value_source(id) != Some(clap::parser::ValueSource::CommandLine) {
+                continue;
+            }
+            let Some(raw) = matches.get_raw(id) else {
+                continue;
+            };
+            let mut values = raw
+                .map(|v| Self::parse_str(&v.to_string_lossy()))
+                .collect::<Vec<_>>();
+            let is_multiple = matches!(arg.get_action(), clap::ArgAction::Append)
+                || arg.get_num_args().is_some_and(|n| n.max_values() > 1);
+            let value = match (is_multiple, values.len()) {
+                (false, 1) => values.remove(0),
+                _ => serde_json::Value::Array(values),
+            };
+            Self::insert_path(map, &format!("{}{}", prefix, id), value)?;
+        }
+
+        if let Some((name, sub_matches)) = matches.subcommand()
+            && let Some(sub_command) = command.find_subcommand(name)
+        {
+            let prefix = format!("{}{}.", prefix, name);
+            Self::insert_arg_matches(map, &prefix, sub_command, sub_matches)?;
+        }
+
+        Ok(())
+    }
+
+    // insert value at dotted key path, e.g. `database.port`, creating nested maps.
+    // Value inserted more than once at the same path becomes an array.
+    fn insert_path(
+        map: &mut serde_json::Map<String, serde_json::Value>,
+        key: &str,
+        value: serde_json::Value,
+    ) -> Result<(), ConfigErrorImpl> {
+        let err = || ConfigErrorImpl::ArgsError(format!("invalid argument key: {}", key));
+        let mut segments = key.split('.').collect::<Vec<_>>();
+        let last = segments.pop().ok_or_else(err)?;
+        if last.is_empty() || segments.iter().any(|s| s.is_empty()) {
+            return Err(err());
+        }
+
+        let mut map = map;
+        for segment in segments {
+            let entry = map
+                .entry(segment)
+                .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+            map = match entry {
+                serde_json::Value::Object(v) => v,
+                _ => return Err(err()),
+            };
+        }
+
+        match map.get_mut(last) {
+            None => {
+                map.insert(last.to_string(), value);
+            }
+            Some(serde_json::Value::Object(_)) => return Err(err()),
+            Some(serde_json::Value::Array(v)) => v.push(value),
+            Some(existing) => {
+                let first = existing.take();
+                *existing = serde_json::Value::Array(vec![first, value]);
+            }
+        }
+
+        Ok(())
+    }
+
+    // wrap value at dotted key path into an array, unless it's already one.
+    fn wrap_array(map: &mut serde_json::Map<String, serde_json::Value>, key: &str) {
+        let mut segments = key.split('.');
+        let Some(mut value) = segments.next().and_then(|segment| map.get_mut(segment)) else {
+            return;
+        };
+        for segment in segments {
+            match value.get_mut(segment) {
+                Some(v) => value = v,
+                None => return,
+            }
+        }
+        if !value.is_array() {
+            *value = serde_json::Value::Array(vec![value.take()]);
+        }
     }
 
     fn load_file_to_string(
@@ -284,4 +1919,37 @@ impl ConfigImpl {
             err.to_string()
         ))
     }
+
+    // duplicated key, with both original spellings when they differ from the normalized key paths.
+    fn make_err_duplicate(
+        path: &[String],
+        (first, first_spelling): (&str, &str),
+        (second, second_spelling): (&str, &str),
+    ) -> ConfigErrorImpl {
+        if first == first_spelling && second == second_spelling {
+            return ConfigErrorImpl::DuplicateKey(path.join("."));
+        }
+        ConfigErrorImpl::DuplicateKey(format!(
+            "{} ({}, {})",
+            path.join("."),
+            first_spelling,
+            second_spelling
+        ))
+    }
+
+    fn make_err_json5(err: json5::Error) -> ConfigErrorImpl {
+        match err {
+            json5::Error::Message {
+                msg,
+                location: Some(location),
+            } => ConfigErrorImpl::Json5Error(format!(
+                "line {}, column {}: {}",
+                location.line, location.column, msg
+            )),
+            json5::Error::Message {
+                msg,
+                location: None,
+            } => ConfigErrorImpl::Json5Error(msg),
+        }
+    }
 }
@@ -0,0 +1,101 @@
+// Consul KV source, read through the `/v1/kv` HTTP API.
+
+use std::time::Duration;
+
+use base64::Engine;
+use serde::Deserialize;
+
+use super::config_error_impl::ConfigErrorImpl;
+use super::http_impl::{self, HttpOptions};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct KvPair {
+    key: String,
+    value: Option<String>, // base64, none for folders
+}
+
+// url reading every key under prefix, e.g. `app/config` reads `app/config/*` but not `app/configuration`.
+pub(super) fn url(addr: &str, prefix: &str) -> String {
+    format!(
+        "{}/v1/kv/{}?recurse=true",
+        addr.trim_end_matches('/'),
+        key_prefix(prefix)
+    )
+}
+
+// decoded values by key path relative to prefix, e.g. `app/db/host` into `db.host`, none body is an empty prefix.
+pub(super) fn values(
+    body: Option<&str>,
+    prefix: &str,
+) -> Result<Vec<(Vec<String>, String)>, ConfigErrorImpl> {
+    let Some(body) = body else {
+        return Ok(Vec::new());
+    };
+    let pairs = serde_json::from_str::<Vec<KvPair>>(body)
+        .map_err(|err| ConfigErrorImpl::ConsulError(err.to_string()))?;
+
+    let prefix = key_prefix(prefix);
+    let mut ret = Vec::new();
+    for pair in pairs {
+        let Some(value) = pair.value else {
+            continue;
+        };
+        let key = pair.key.strip_prefix(&prefix).unwrap_or(&pair.key);
+        let path: Vec<String> = key
+            .split('/')
+            .filter(|k| !k.is_empty())
+            .map(String::from)
+            .collect();
+        if path.is_empty() {
+            continue;
+        }
+        let value = base64::engine::general_purpose::STANDARD
+            .decode(value)
+            .ok()
+            .and_then(|v| String::from_utf8(v).ok())
+            .ok_or_else(|| {
+                ConfigErrorImpl::ConsulError(format!("invalid value of key {}", pair.key))
+            })?;
+        ret.push((path, value));
+    }
+    Ok(ret)
+}
+
+// blocking query, returning the index of prefix once it's greater than index, or when wait has elapsed.
+pub(super) fn watch(
+    addr: &str,
+    prefix: &str,
+    index: u64,
+    wait: Duration,
+    options: &HttpOptions,
+) -> Result<u64, ConfigErrorImpl> {
+    let url = format!(
+        "{}&index={}&wait={}ms",
+        url(addr, prefix),
+        index,
+        wait.as_millis()
+    );
+    let mut options = options.clone();
+    // consul adds up to wait / 16 of jitter to the wait
+    options.timeout = options.timeout.map(|timeout| timeout + wait + wait / 16);
+
+    let reply = http_impl::request(&url, None, &options)?;
+    if !matches!(reply.status, 200 | 404) {
+        return Err(ConfigErrorImpl::HttpError(format!(
+            "{}: status {}",
+            url, reply.status
+        )));
+    }
+    reply
+        .header("x-consul-index")
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| ConfigErrorImpl::ConsulError(format!("{}: missing X-Consul-Index", url)))
+}
+
+fn key_prefix(prefix: &str) -> String {
+    match prefix.trim_matches('/') {
+        "" => String::new(),
+        prefix => format!("{}/", prefix),
+    }
+}
@@ -0,0 +1,162 @@
+// encrypted values `ENC[AES256_GCM,data:..,iv:..,tag:..,type:..]`, the value format of SOPS,
+// with AES-256-GCM and a 32 bytes iv, so values encrypted by SOPS can be decrypted with their data key.
+
+use aes_gcm::aead::consts::U32;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{AeadInPlace, KeyInit, OsRng};
+use aes_gcm::aes::Aes256;
+use aes_gcm::{AesGcm, Nonce, Tag};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+use super::config_error_impl::ConfigErrorImpl;
+
+type Cipher = AesGcm<Aes256, U32>;
+
+pub(super) fn generate_key() -> String {
+    let mut key = [0; 32];
+    OsRng.fill_bytes(&mut key);
+    STANDARD.encode(key)
+}
+
+// key of base64 encoded 32 bytes.
+pub(super) fn parse_key(key: &str) -> Result<Vec<u8>, String> {
+    STANDARD
+        .decode(key.trim())
+        .ok()
+        .filter(|key| key.len() == 32)
+        .ok_or_else(|| "key must be 32 bytes encoded in base64".to_string())
+}
+
+pub(super) fn is_encrypted(value: &str) -> bool {
+    value.starts_with("ENC[") && value.ends_with(']')
+}
+
+// encrypt scalar value, its type is kept to restore it on decryption.
+pub(super) fn encrypt(key: &[u8], value: &serde_json::Value, aad: &[u8]) -> Result<String, String> {
+    let (plain, ty) = match value {
+        serde_json::Value::String(v) => (v.clone(), "str"),
+        serde_json::Value::Number(v) if v.is_f64() => (v.to_string(), "float"),
+        serde_json::Value::Number(v) => (v.to_string(), "int"),
+        serde_json::Value::Bool(v) => (v.to_string(), "bool"),
+        _ => return Err("only strings, numbers and booleans can be encrypted".to_string()),
+    };
+    let cipher = Cipher::new_from_slice(key).map_err(|err| err.to_string())?;
+    let mut iv = [0; 32];
+    OsRng.fill_bytes(&mut iv);
+    let mut data = plain.into_bytes();
+    let tag = cipher
+        .encrypt_in_place_detached(Nonce::from_slice(&iv), aad, &mut data)
+        .map_err(|err| err.to_string())?;
+    Ok(format!(
+        "ENC[AES256_GCM,data:{},iv:{},tag:{},type:{}]",
+        STANDARD.encode(data),
+        STANDARD.encode(iv),
+        STANDARD.encode(tag),
+        ty
+    ))
+}
+
+pub(super) fn decrypt(key: &[u8], value: &str, aad: &[u8]) -> Result<serde_json::Value, String> {
+    let (plain, ty) = decrypt_plain(key, value, aad)?;
+    typed(plain, &ty)
+}
+
+// decrypt value into its plain text and type.
+pub(super) fn decrypt_plain(
+    key: &[u8],
+    value: &str,
+    aad: &[u8],
+) -> Result<(String, String), String> {
+    let inner = value
+        .strip_prefix("ENC[AES256_GCM,")
+        .and_then(|v| v.strip_suffix(']'))
+        .ok_or("unsupported encrypted value, expected `ENC[AES256_GCM,...]`")?;
+    let mut data = None;
+    let mut iv = None;
+    let mut tag = None;
+    let mut ty = "str";
+    for field in inner.split(',') {
+        match field.split_once(':') {
+            Some(("data", v)) => data = Some(decode(v)?),
+            Some(("iv", v)) => iv = Some(decode(v)?),
+            Some(("tag", v)) => tag = Some(decode(v)?),
+            Some(("type", v)) => ty = v,
+            _ => return Err(format!("invalid field {}", field)),
+        }
+    }
+    let (Some(mut data), Some(iv), Some(tag)) = (data, iv, tag) else {
+        return Err("expected `data`, `iv` and `tag` fields".to_string());
+    };
+    if iv.len() != 32 || tag.len() != 16 {
+        return Err("invalid iv or tag length".to_string());
+    }
+
+    let cipher = Cipher::new_from_slice(key).map_err(|err| err.to_string())?;
+    cipher
+        .decrypt_in_place_detached(
+            Nonce::from_slice(&iv),
+            aad,
+            &mut data,
+            Tag::from_slice(&tag),
+        )
+        .map_err(|_| "wrong key or tampered value".to_string())?;
+    let plain = String::from_utf8(data).map_err(|err| err.to_string())?;
+    Ok((plain, ty.to_string()))
+}
+
+// value of plain text of type, e.g. `5432` of type `int` into number.
+pub(super) fn typed(plain: String, ty: &str) -> Result<serde_json::Value, String> {
+    let invalid = || format!("invalid {} value", ty);
+    match ty {
+        "str" | "bytes" => Ok(serde_json::Value::String(plain)),
+        "int" => plain
+            .parse::<i64>()
+            .map(serde_json::Value::from)
+            .map_err(|_| invalid()),
+        "float" => plain
+            .parse::<f64>()
+            .map(serde_json::Value::from)
+            .map_err(|_| invalid()),
+        "bool" => match plain.to_ascii_lowercase().as_str() {
+            "true" => Ok(serde_json::Value::Bool(true)),
+            "false" => Ok(serde_json::Value::Bool(false)),
+            _ => Err(invalid()),
+        },
+        _ => Err(format!("unsupported type {}", ty)),
+    }
+}
+
+// decrypt every encrypted string inside value, at dotted key path.
+pub(super) fn decrypt_all(
+    key: &[u8],
+    value: &mut serde_json::Value,
+    path: &mut Vec<String>,
+) -> Result<(), ConfigErrorImpl> {
+    match value {
+        serde_json::Value::String(v) if is_encrypted(v) => {
+            *value = decrypt(key, v, &[])
+                .map_err(|err| ConfigErrorImpl::DecryptionError(path.join("."), err))?;
+        }
+        serde_json::Value::Array(arr) => {
+            for (i, v) in arr.iter_mut().enumerate() {
+                path.push(i.to_string());
+                decrypt_all(key, v, path)?;
+                path.pop();
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for (k, v) in map.iter_mut() {
+                path.push(k.clone());
+                decrypt_all(key, v, path)?;
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn decode(v: &str) -> Result<Vec<u8>, String> {
+    STANDARD.decode(v).map_err(|err| err.to_string())
+}
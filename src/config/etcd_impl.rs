@@ -0,0 +1,167 @@
+// etcd v3 source, read through the HTTP/JSON gateway.
+
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::Deserialize;
+
+use super::config_error_impl::ConfigErrorImpl;
+use super::http_impl::{self, HttpOptions};
+
+// int64 fields are strings in the gateway JSON
+#[derive(Deserialize)]
+struct Header {
+    revision: String,
+}
+
+#[derive(Deserialize)]
+struct KeyValue {
+    key: String,
+    #[serde(default)]
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct RangeResponse {
+    header: Header,
+    #[serde(default)]
+    kvs: Vec<KeyValue>,
+}
+
+#[derive(Deserialize)]
+struct WatchMessage {
+    result: Option<WatchResponse>,
+    error: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct WatchResponse {
+    header: Header,
+    #[serde(default)]
+    events: Vec<serde_json::Value>,
+    #[serde(default)]
+    canceled: bool,
+}
+
+pub(super) fn range_url(addr: &str) -> String {
+    format!("{}/v3/kv/range", addr.trim_end_matches('/'))
+}
+
+// range request of every key under prefix, e.g. `/app` reads `/app/*` but not `/application`.
+pub(super) fn range_body(prefix: &str) -> String {
+    let (key, range_end) = range(prefix);
+    serde_json::json!({ "key": key, "range_end": range_end }).to_string()
+}
+
+// decoded values by key path relative to prefix, e.g. `/app/db/host` into `db.host`.
+pub(super) fn values(
+    body: &str,
+    prefix: &str,
+) -> Result<Vec<(Vec<String>, String)>, ConfigErrorImpl> {
+    let response = serde_json::from_str::<RangeResponse>(body)
+        .map_err(|err| ConfigErrorImpl::EtcdError(err.to_string()))?;
+
+    let prefix = key_prefix(prefix);
+    let mut ret = Vec::new();
+    for kv in response.kvs {
+        let key = decode(&kv.key)
+            .ok_or_else(|| ConfigErrorImpl::EtcdError(format!("invalid key {}", kv.key)))?;
+        let value = decode(&kv.value)
+            .ok_or_else(|| ConfigErrorImpl::EtcdError(format!("invalid value of key {}", key)))?;
+        let path: Vec<String> = key
+            .strip_prefix(&prefix)
+            .unwrap_or(&key)
+            .split('/')
+            .filter(|k| !k.is_empty())
+            .map(String::from)
+            .collect();
+        if path.is_empty() {
+            continue;
+        }
+        ret.push((path, value));
+    }
+    Ok(ret)
+}
+
+// watch prefix for changes after revision, returning the revision of the first change, or revision when wait has elapsed.
+// revision `0` returns the current revision right away.
+pub(super) fn watch(
+    addr: &str,
+    prefix: &str,
+    revision: u64,
+    wait: Duration,
+    options: &HttpOptions,
+) -> Result<u64, ConfigErrorImpl> {
+    let addr = addr.trim_end_matches('/');
+    if revision == 0 {
+        let body = http_impl::post(&range_url(addr), &range_body(prefix), options)?;
+        let response = serde_json::from_str::<RangeResponse>(&body)
+            .map_err(|err| ConfigErrorImpl::EtcdError(err.to_string()))?;
+        return parse_revision(&response.header);
+    }
+
+    let (key, range_end) = range(prefix);
+    let body = serde_json::json!({
+        "create_request": {
+            "key": key,
+            "range_end": range_end,
+            "start_revision": (revision + 1).to_string(),
+        }
+    })
+    .to_string();
+    let mut options = options.clone();
+    options.timeout = Some(wait);
+
+    let url = format!("{}/v3/watch", addr);
+    let changed = http_impl::stream(&url, &body, &options, |line| {
+        let message = serde_json::from_str::<WatchMessage>(line)
+            .map_err(|err| ConfigErrorImpl::EtcdError(err.to_string()))?;
+        if let Some(err) = message.error {
+            return Err(ConfigErrorImpl::EtcdError(err.to_string()));
+        }
+        match message.result {
+            // canceled when revision has been compacted, changes may have been missed
+            Some(result) if !result.events.is_empty() || result.canceled => {
+                parse_revision(&result.header).map(Some)
+            }
+            _ => Ok(None),
+        }
+    })?;
+    Ok(changed.unwrap_or(revision))
+}
+
+fn parse_revision(header: &Header) -> Result<u64, ConfigErrorImpl> {
+    header
+        .revision
+        .parse()
+        .map_err(|_| ConfigErrorImpl::EtcdError(format!("invalid revision {}", header.revision)))
+}
+
+// base64 key and range end of prefix, the range end is the prefix with its last byte incremented.
+fn range(prefix: &str) -> (String, String) {
+    let key = key_prefix(prefix).into_bytes();
+    if key.is_empty() {
+        // every key
+        return (STANDARD.encode([0]), STANDARD.encode([0]));
+    }
+    let mut range_end = key.clone();
+    while let Some(last) = range_end.pop() {
+        if last < 0xff {
+            range_end.push(last + 1);
+            break;
+        }
+    }
+    (STANDARD.encode(key), STANDARD.encode(range_end))
+}
+
+fn decode(v: &str) -> Option<String> {
+    String::from_utf8(STANDARD.decode(v).ok()?).ok()
+}
+
+fn key_prefix(prefix: &str) -> String {
+    match prefix.trim_end_matches('/') {
+        "" => String::new(),
+        prefix => format!("{}/", prefix),
+    }
+}
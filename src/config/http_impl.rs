@@ -0,0 +1,234 @@
+// fetching of remote sources over HTTP/HTTPS, with retries and ETag cache on disk.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::config_error_impl::ConfigErrorImpl;
+
+// delay before the first retry, doubled on each next retry.
+const RETRY_DELAY: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Debug, Default)]
+pub(super) struct HttpOptions {
+    pub timeout: Option<Duration>,
+    pub retries: u32,
+    pub headers: Vec<(String, String)>,
+    pub cache: Option<PathBuf>, // cached document, with its ETag in `<cache>.etag`
+}
+
+pub(super) struct Reply {
+    pub status: u16,
+    pub headers: ureq::http::HeaderMap,
+    pub body: String,
+}
+
+impl Reply {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+}
+
+// fetch the document at url, or its cached copy when not modified or when the server is unreachable.
+pub(super) fn get(url: &str, options: &HttpOptions) -> Result<String, ConfigErrorImpl> {
+    fetch(url, None, options)?
+        .ok_or_else(|| ConfigErrorImpl::HttpError(format!("{}: status 404", url)))
+}
+
+// fetch the document like `get`, posting body, e.g. for the etcd gateway.
+#[cfg(feature = "etcd")]
+pub(super) fn post(
+    url: &str,
+    body: &str,
+    options: &HttpOptions,
+) -> Result<String, ConfigErrorImpl> {
+    fetch(url, Some(body), options)?
+        .ok_or_else(|| ConfigErrorImpl::HttpError(format!("{}: status 404", url)))
+}
+
+// fetch the document like `get`, posting body if any, none when not found.
+pub(super) fn fetch(
+    url: &str,
+    body: Option<&str>,
+    options: &HttpOptions,
+) -> Result<Option<String>, ConfigErrorImpl> {
+    // only revalidate when the cached copy is still there
+    let etag = options
+        .cache
+        .as_ref()
+        .filter(|cache| body.is_none() && cache.exists())
+        .and_then(|cache| std::fs::read_to_string(etag_path(cache)).ok());
+
+    let reply = match send(url, body, options, etag.as_deref()) {
+        Ok(reply) => reply,
+        Err(err) => {
+            return cached(options)
+                .map(Some)
+                .ok_or(ConfigErrorImpl::HttpError(err));
+        }
+    };
+    match reply.status {
+        304 => cached(options).map(Some).ok_or_else(|| {
+            ConfigErrorImpl::HttpError(format!("{}: not modified, cache not found", url))
+        }),
+        404 => Ok(None),
+        200..=299 => {
+            store(options, &reply.body, reply.header("etag"));
+            Ok(Some(reply.body))
+        }
+        status => Err(ConfigErrorImpl::HttpError(format!(
+            "{}: status {}",
+            url, status
+        ))),
+    }
+}
+
+// send request without cache, answering any status other than 5xx.
+#[cfg(feature = "consul")]
+pub(super) fn request(
+    url: &str,
+    body: Option<&str>,
+    options: &HttpOptions,
+) -> Result<Reply, ConfigErrorImpl> {
+    send(url, body, options, None).map_err(ConfigErrorImpl::HttpError)
+}
+
+// post body and read the streamed response line by line until f returns a value,
+// none when the response ends or the timeout elapses.
+#[cfg(feature = "etcd")]
+pub(super) fn stream<T>(
+    url: &str,
+    body: &str,
+    options: &HttpOptions,
+    mut f: impl FnMut(&str) -> Result<Option<T>, ConfigErrorImpl>,
+) -> Result<Option<T>, ConfigErrorImpl> {
+    use std::io::BufRead;
+
+    let response = with_headers(agent(options).post(url), options, None).send(body);
+    let mut response = match response {
+        Ok(response) => response,
+        Err(ureq::Error::Timeout(_)) => return Ok(None),
+        Err(err) => return Err(ConfigErrorImpl::HttpError(format!("{}: {}", url, err))),
+    };
+    let status = response.status().as_u16();
+    if !(200..=299).contains(&status) {
+        return Err(ConfigErrorImpl::HttpError(format!(
+            "{}: status {}",
+            url, status
+        )));
+    }
+    let reader = std::io::BufReader::new(response.body_mut().as_reader());
+    for line in reader.lines() {
+        // timeout elapsed or connection closed
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(v) = f(&line)? {
+            return Ok(Some(v));
+        }
+    }
+    Ok(None)
+}
+
+// send request, retrying transport errors and 5xx statuses, error when the server is unreachable.
+fn send(
+    url: &str,
+    body: Option<&str>,
+    options: &HttpOptions,
+    etag: Option<&str>,
+) -> Result<Reply, String> {
+    let agent = agent(options);
+    let mut delay = RETRY_DELAY;
+    let mut retries = options.retries;
+    loop {
+        match send_once(&agent, url, body, options, etag) {
+            Err(_) if retries > 0 => {
+                std::thread::sleep(delay);
+                delay *= 2;
+                retries -= 1;
+            }
+            ret => return ret,
+        }
+    }
+}
+
+fn agent(options: &HttpOptions) -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(options.timeout)
+        .http_status_as_error(false)
+        .build()
+        .into()
+}
+
+fn send_once(
+    agent: &ureq::Agent,
+    url: &str,
+    body: Option<&str>,
+    options: &HttpOptions,
+    etag: Option<&str>,
+) -> Result<Reply, String> {
+    let response = match body {
+        Some(body) => with_headers(agent.post(url), options, etag).send(body),
+        None => with_headers(agent.get(url), options, etag).call(),
+    };
+    let mut response = response.map_err(|err| format!("{}: {}", url, err))?;
+
+    let status = response.status().as_u16();
+    if (500..=599).contains(&status) {
+        return Err(format!("{}: status {}", url, status));
+    }
+    let body = response
+        .body_mut()
+        .read_to_string()
+        .map_err(|err| format!("{}: {}", url, err))?;
+    Ok(Reply {
+        status,
+        headers: response.headers().clone(),
+        body,
+    })
+}
+
+fn with_headers<B>(
+    mut req: ureq::RequestBuilder<B>,
+    options: &HttpOptions,
+    etag: Option<&str>,
+) -> ureq::RequestBuilder<B> {
+    for (name, value) in &options.headers {
+        req = req.header(name, value);
+    }
+    if let Some(etag) = etag {
+        req = req.header("If-None-Match", etag);
+    }
+    req
+}
+
+fn cached(options: &HttpOptions) -> Option<String> {
+    std::fs::read_to_string(options.cache.as_ref()?).ok()
+}
+
+// caching is best effort, the fetched document is used even if it can't be written.
+fn store(options: &HttpOptions, body: &str, etag: Option<&str>) {
+    let Some(cache) = &options.cache else {
+        return;
+    };
+    if std::fs::write(cache, body).is_err() {
+        return;
+    }
+    match etag {
+        Some(etag) => {
+            let _ = std::fs::write(etag_path(cache), etag);
+        }
+        None => {
+            let _ = std::fs::remove_file(etag_path(cache));
+        }
+    }
+}
+
+fn etag_path(cache: &Path) -> PathBuf {
+    let mut path = OsString::from(cache.as_os_str());
+    path.push(".etag");
+    PathBuf::from(path)
+}
@@ -0,0 +1,48 @@
+// JSON Schema of config types, and validation of merged configs against schemas.
+
+use super::config_error_impl::ConfigErrorImpl;
+
+pub(super) fn generate<T: schemars::JsonSchema + ?Sized>() -> serde_json::Value {
+    schemars::SchemaGenerator::default()
+        .into_root_schema_for::<T>()
+        .to_value()
+}
+
+// failures of instance against schema, with the JSON pointer and key path of the failing value, without the value itself.
+pub(super) fn violations(
+    schema: &serde_json::Value,
+    instance: &serde_json::Value,
+) -> Result<Vec<(String, Vec<String>, String)>, ConfigErrorImpl> {
+    let validator = jsonschema::validator_for(schema)
+        .map_err(|err| ConfigErrorImpl::BuildError(format!("invalid schema: {}", err)))?;
+    Ok(validator
+        .iter_errors(instance)
+        .map(|err| {
+            let pointer = err.instance_path.to_string();
+            let path = segments(&pointer);
+            // masked, values may be secrets decrypted before validation
+            (pointer, path, err.masked().to_string())
+        })
+        .collect())
+}
+
+// value at key path inside value, keys of tables or indexes of arrays.
+pub(super) fn get<'a>(
+    value: &'a serde_json::Value,
+    path: &[String],
+) -> Option<&'a serde_json::Value> {
+    path.iter().try_fold(value, |value, key| match value {
+        serde_json::Value::Object(map) => map.get(key),
+        serde_json::Value::Array(arr) => arr.get(key.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+// unescaped segments of JSON pointer, e.g. `/paths/a~1b` into `paths`, `a/b`.
+fn segments(pointer: &str) -> Vec<String> {
+    pointer
+        .split('/')
+        .skip(1)
+        .map(|s| s.replace("~1", "/").replace("~0", "~"))
+        .collect()
+}
@@ -0,0 +1,174 @@
+// SOPS encrypted files, with the data key encrypted for age recipients.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use sha2::{Digest, Sha512};
+
+use super::config_error_impl::ConfigErrorImpl;
+use super::encryption_impl;
+
+#[derive(Deserialize)]
+struct Metadata {
+    #[serde(default)]
+    age: Vec<AgeKey>,
+    lastmodified: String,
+    mac: String,
+    #[serde(default)]
+    mac_only_encrypted: bool,
+}
+
+#[derive(Deserialize)]
+struct AgeKey {
+    enc: String, // armored age file of the data key
+}
+
+// identity file given, or the one used by sops: `SOPS_AGE_KEY_FILE`, then `sops/age/keys.txt` in user config dir.
+// env vars are looked up with env_var, e.g. injected env vars first.
+pub(super) fn identity_file(
+    file_path: Option<&Path>,
+    env_var: impl Fn(&str) -> Option<String>,
+) -> Result<PathBuf, ConfigErrorImpl> {
+    if let Some(file_path) = file_path {
+        return Ok(file_path.to_path_buf());
+    }
+    if let Some(file_path) = env_var("SOPS_AGE_KEY_FILE") {
+        return Ok(PathBuf::from(file_path));
+    }
+    env_var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env_var("HOME").map(|home| Path::new(&home).join(".config")))
+        .map(|dir| dir.join("sops").join("age").join("keys.txt"))
+        .ok_or_else(|| ConfigErrorImpl::SopsError("age identity file not found".to_string()))
+}
+
+// decrypt document with the data key, after verifying its MAC, without the `sops` metadata.
+// document is read into yaml value for both yaml and json, keeping the order of keys the MAC is computed in.
+pub(super) fn decrypt(
+    document: serde_yaml::Value,
+    identity_file: &Path,
+) -> Result<serde_json::Value, ConfigErrorImpl> {
+    let serde_yaml::Value::Mapping(mut root) = document else {
+        return Err(ConfigErrorImpl::SopsError(
+            "expected a table at root".to_string(),
+        ));
+    };
+    let metadata = root
+        .remove("sops")
+        .ok_or_else(|| ConfigErrorImpl::SopsError("sops metadata not found".to_string()))?;
+    let metadata = serde_yaml::from_value::<Metadata>(metadata)
+        .map_err(|err| ConfigErrorImpl::SopsError(err.to_string()))?;
+    let key = data_key(&metadata, identity_file)?;
+
+    let mut hasher = Sha512::new();
+    let tree = decrypt_value(
+        &key,
+        serde_yaml::Value::Mapping(root),
+        &mut Vec::new(),
+        &mut hasher,
+        metadata.mac_only_encrypted,
+    )?;
+    let mac: String = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect();
+    let (expected, _) =
+        encryption_impl::decrypt_plain(&key, &metadata.mac, metadata.lastmodified.as_bytes())
+            .map_err(|err| ConfigErrorImpl::SopsError(format!("MAC: {}", err)))?;
+    if mac != expected {
+        return Err(ConfigErrorImpl::SopsError(
+            "MAC mismatch, the file has been modified".to_string(),
+        ));
+    }
+    Ok(tree)
+}
+
+fn data_key(metadata: &Metadata, identity_file: &Path) -> Result<Vec<u8>, ConfigErrorImpl> {
+    let identities = age::IdentityFile::from_file(identity_file.to_string_lossy().into_owned())
+        .map_err(|err| ConfigErrorImpl::FileError(err.to_string()))?
+        .into_identities()
+        .map_err(|err| ConfigErrorImpl::SopsError(err.to_string()))?;
+
+    for age_key in &metadata.age {
+        let Ok(decryptor) =
+            age::Decryptor::new_buffered(age::armor::ArmoredReader::new(age_key.enc.as_bytes()))
+        else {
+            continue;
+        };
+        let Ok(mut reader) = decryptor.decrypt(identities.iter().map(|i| i.as_ref())) else {
+            continue;
+        };
+        let mut key = Vec::new();
+        if reader.read_to_end(&mut key).is_ok() && key.len() == 32 {
+            return Ok(key);
+        }
+    }
+    Err(ConfigErrorImpl::SopsError(
+        "no age identity can decrypt the data key".to_string(),
+    ))
+}
+
+// decrypt leaves with the key path as additional data, e.g. `database:password:`, items of arrays share the path of the array,
+// and hash their plain text in document order.
+fn decrypt_value(
+    key: &[u8],
+    value: serde_yaml::Value,
+    path: &mut Vec<String>,
+    hasher: &mut Sha512,
+    mac_only_encrypted: bool,
+) -> Result<serde_json::Value, ConfigErrorImpl> {
+    match value {
+        serde_yaml::Value::Mapping(mapping) => {
+            let mut map = serde_json::Map::new();
+            for (k, v) in mapping {
+                let k = match k {
+                    serde_yaml::Value::String(k) => k,
+                    k => serde_yaml::to_string(&k)
+                        .map_err(|err| ConfigErrorImpl::SopsError(err.to_string()))?
+                        .trim_end()
+                        .to_string(),
+                };
+                path.push(k.clone());
+                let v = decrypt_value(key, v, path, hasher, mac_only_encrypted)?;
+                path.pop();
+                map.insert(k, v);
+            }
+            Ok(serde_json::Value::Object(map))
+        }
+        serde_yaml::Value::Sequence(seq) => seq
+            .into_iter()
+            .map(|v| decrypt_value(key, v, path, hasher, mac_only_encrypted))
+            .collect::<Result<_, _>>()
+            .map(serde_json::Value::Array),
+        serde_yaml::Value::Tagged(tagged) => {
+            decrypt_value(key, tagged.value, path, hasher, mac_only_encrypted)
+        }
+        serde_yaml::Value::String(v) if encryption_impl::is_encrypted(&v) => {
+            let aad = format!("{}:", path.join(":"));
+            let err = |err| ConfigErrorImpl::DecryptionError(path.join("."), err);
+            let (plain, ty) =
+                encryption_impl::decrypt_plain(key, &v, aad.as_bytes()).map_err(err)?;
+            hasher.update(plain.as_bytes());
+            encryption_impl::typed(plain, &ty).map_err(err)
+        }
+        value => {
+            if !mac_only_encrypted {
+                hasher.update(plain_bytes(&value));
+            }
+            serde_yaml::from_value(value).map_err(|err| ConfigErrorImpl::SopsError(err.to_string()))
+        }
+    }
+}
+
+// bytes of unencrypted leaf hashed by sops, e.g. `True` for booleans.
+fn plain_bytes(value: &serde_yaml::Value) -> Vec<u8> {
+    match value {
+        serde_yaml::Value::String(v) => v.as_bytes().to_vec(),
+        serde_yaml::Value::Bool(true) => b"True".to_vec(),
+        serde_yaml::Value::Bool(false) => b"False".to_vec(),
+        serde_yaml::Value::Number(v) => v.to_string().into_bytes(),
+        _ => Vec::new(),
+    }
+}
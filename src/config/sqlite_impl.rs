@@ -0,0 +1,135 @@
+// SQLite source, reading `key, value` rows, with optional `type`, of a table or query.
+
+use std::path::Path;
+
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+
+use super::config_error_impl::ConfigErrorImpl;
+
+pub(super) fn table_query(table: &str) -> String {
+    format!("SELECT * FROM \"{}\"", table.replace('"', "\"\""))
+}
+
+// values of rows by dotted key path, untyped text values are parsed with parse.
+pub(super) fn values(
+    file_path: &Path,
+    query: &str,
+    parse: fn(&str) -> serde_json::Value,
+) -> Result<Vec<(Vec<String>, serde_json::Value)>, ConfigErrorImpl> {
+    let conn = open(file_path)?;
+    let mut stmt = conn.prepare(query).map_err(make_err)?;
+    let column = |name: &str| {
+        stmt.column_names()
+            .iter()
+            .position(|c| c.eq_ignore_ascii_case(name))
+    };
+    let (Some(key), Some(value)) = (column("key"), column("value")) else {
+        return Err(ConfigErrorImpl::SqliteError(format!(
+            "expected `key` and `value` columns in {}",
+            query
+        )));
+    };
+    let ty = column("type");
+
+    let mut ret = Vec::new();
+    let mut rows = stmt.query([]).map_err(make_err)?;
+    while let Some(row) = rows.next().map_err(make_err)? {
+        let k = row.get::<_, String>(key).map_err(make_err)?;
+        let t = match ty {
+            Some(ty) => row.get::<_, Option<String>>(ty).map_err(make_err)?,
+            None => None,
+        };
+        let v = to_json(
+            &k,
+            row.get_ref(value).map_err(make_err)?,
+            t.as_deref(),
+            parse,
+        )?;
+        ret.push((k.split('.').map(String::from).collect(), v));
+    }
+    Ok(ret)
+}
+
+// watch changes committed to the database by other connections with `PRAGMA data_version`.
+pub(super) struct Watcher {
+    conn: Connection,
+    version: i64,
+}
+
+impl Watcher {
+    pub fn new(file_path: &Path) -> Result<Self, ConfigErrorImpl> {
+        let conn = open(file_path)?;
+        let version = data_version(&conn)?;
+        Ok(Self { conn, version })
+    }
+
+    pub fn changed(&mut self) -> Result<bool, ConfigErrorImpl> {
+        let version = data_version(&self.conn)?;
+        let changed = version != self.version;
+        self.version = version;
+        Ok(changed)
+    }
+}
+
+fn open(file_path: &Path) -> Result<Connection, ConfigErrorImpl> {
+    Connection::open_with_flags(
+        file_path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(make_err)
+}
+
+fn data_version(conn: &Connection) -> Result<i64, ConfigErrorImpl> {
+    conn.query_row("PRAGMA data_version", [], |row| row.get(0))
+        .map_err(make_err)
+}
+
+// value of row, by its type if any: `string`, `integer`, `float`, `bool` or `json`.
+fn to_json(
+    key: &str,
+    value: ValueRef,
+    ty: Option<&str>,
+    parse: fn(&str) -> serde_json::Value,
+) -> Result<serde_json::Value, ConfigErrorImpl> {
+    let invalid = || ConfigErrorImpl::SqliteError(format!("invalid value of key {}", key));
+    let text = match value {
+        ValueRef::Null => return Ok(serde_json::Value::Null),
+        ValueRef::Integer(v) => v.to_string(),
+        ValueRef::Real(v) => v.to_string(),
+        ValueRef::Text(v) => String::from_utf8(v.to_vec()).map_err(|_| invalid())?,
+        ValueRef::Blob(_) => return Err(invalid()),
+    };
+    let Some(ty) = ty else {
+        return Ok(match value {
+            ValueRef::Integer(v) => serde_json::Value::from(v),
+            ValueRef::Real(v) => serde_json::Value::from(v),
+            _ => parse(&text),
+        });
+    };
+    match ty.to_ascii_lowercase().as_str() {
+        "string" | "text" => Ok(serde_json::Value::String(text)),
+        "integer" | "int" => text
+            .parse::<i64>()
+            .map(serde_json::Value::from)
+            .map_err(|_| invalid()),
+        "float" | "real" => text
+            .parse::<f64>()
+            .map(serde_json::Value::from)
+            .map_err(|_| invalid()),
+        "bool" | "boolean" => match text.as_str() {
+            "true" | "1" => Ok(serde_json::Value::Bool(true)),
+            "false" | "0" => Ok(serde_json::Value::Bool(false)),
+            _ => Err(invalid()),
+        },
+        "json" => serde_json::from_str(&text).map_err(|_| invalid()),
+        _ => Err(ConfigErrorImpl::SqliteError(format!(
+            "unknown type {} of key {}",
+            ty, key
+        ))),
+    }
+}
+
+fn make_err(err: rusqlite::Error) -> ConfigErrorImpl {
+    ConfigErrorImpl::SqliteError(err.to_string())
+}
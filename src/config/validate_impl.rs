@@ -0,0 +1,47 @@
+// rules generated by `#[derive(validator::Validate)]`, flattened into failures by key path.
+
+use validator::{ValidationErrors, ValidationErrorsKind};
+
+use super::Validator;
+
+pub(super) fn add_errors(v: &mut Validator, errors: &ValidationErrors) {
+    // sorted by field for stable order, the errors of validator are in a hash map
+    let mut fields = errors.errors().iter().collect::<Vec<_>>();
+    fields.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (field, kind) in fields {
+        // `__all__` for rules of the whole struct, e.g. `#[validate(schema(..))]`
+        let key = match field.as_ref() {
+            "__all__" => "",
+            field => field,
+        };
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                for err in errors {
+                    let message = match &err.message {
+                        Some(message) => message.to_string(),
+                        None => err.code.to_string(),
+                    };
+                    v.error(key, message);
+                }
+            }
+            ValidationErrorsKind::Struct(errors) => {
+                v.nested(key, &Derived(errors));
+            }
+            ValidationErrorsKind::List(items) => {
+                for (i, errors) in items {
+                    v.nested(&format!("{}.{}", key, i), &Derived(errors));
+                }
+            }
+        }
+    }
+}
+
+// nested errors, validated again at their key path
+struct Derived<'a>(&'a ValidationErrors);
+
+impl super::Validate for Derived<'_> {
+    fn validate(&self, v: &mut Validator) {
+        add_errors(v, self.0);
+    }
+}
@@ -0,0 +1,71 @@
+// HashiCorp Vault KV v2 source, read through the HTTP API.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use super::VaultMetadata;
+use super::config_error_impl::ConfigErrorImpl;
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(default)]
+    lease_id: String,
+    #[serde(default)]
+    lease_duration: u64,
+    #[serde(default)]
+    renewable: bool,
+    data: Data,
+}
+
+#[derive(Deserialize)]
+struct Data {
+    data: Option<serde_json::Map<String, serde_json::Value>>, // none for deleted versions
+    metadata: Option<Metadata>,
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+    #[serde(default)]
+    version: u64,
+    #[serde(default)]
+    created_time: String,
+}
+
+// url of the latest version of secret at path of KV v2 engine mounted at mount.
+pub(super) fn url(addr: &str, mount: &str, path: &str) -> String {
+    format!(
+        "{}/v1/{}/data/{}",
+        addr.trim_end_matches('/'),
+        mount.trim_matches('/'),
+        path.trim_matches('/')
+    )
+}
+
+// fields of the secret, `data.data` of the response.
+pub(super) fn data(
+    body: &str,
+) -> Result<serde_json::Map<String, serde_json::Value>, ConfigErrorImpl> {
+    parse(body)?
+        .data
+        .data
+        .ok_or_else(|| ConfigErrorImpl::VaultError("secret has no data".to_string()))
+}
+
+pub(super) fn metadata(url: &str, body: &str) -> Result<VaultMetadata, ConfigErrorImpl> {
+    let response = parse(body)?;
+    let metadata = response.data.metadata;
+    Ok(VaultMetadata {
+        url: url.to_string(),
+        version: metadata.as_ref().map(|m| m.version).unwrap_or_default(),
+        created_time: metadata.map(|m| m.created_time).unwrap_or_default(),
+        lease_id: response.lease_id,
+        lease_duration: Duration::from_secs(response.lease_duration),
+        renewable: response.renewable,
+    })
+}
+
+fn parse(body: &str) -> Result<Response, ConfigErrorImpl> {
+    serde_json::from_str::<Response>(body)
+        .map_err(|err| ConfigErrorImpl::VaultError(err.to_string()))
+}
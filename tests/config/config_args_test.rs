@@ -0,0 +1,171 @@
+// test configs from command-line arguments
+
+use serde::{Deserialize, Serialize};
+
+use configrs::config::*;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Database {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Cfg {
+    database: Database,
+    #[serde(rename = "feature-x")]
+    feature_x: bool,
+    #[serde(rename = "feature-y")]
+    feature_y: bool,
+    hosts: Vec<String>,
+}
+
+// parse nested keys, flags and repeated flags
+#[test]
+fn test_args_success() {
+    let args = [
+        "program",
+        "--database.host=localhost",
+        "--database.port=5432",
+        "--feature-x",
+        "--no-feature-y",
+        "positional",
+        "--hosts=a",
+        "--hosts=b",
+        "--hosts=c",
+    ];
+    let cfg = Config::new().with_args(args).build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.database.host, "localhost");
+    assert_eq!(cfg.database.port, 5432);
+    assert!(cfg.feature_x);
+    assert!(!cfg.feature_y);
+    assert_eq!(cfg.hosts, vec!["a", "b", "c"]);
+}
+
+// last boolean flag wins, and list keys given once form an array
+#[test]
+fn test_args_last_flag_and_list_success() {
+    let args = [
+        "--database.host=localhost",
+        "--database.port=5432",
+        "--feature-x",
+        "--no-feature-x",
+        "--no-feature-y",
+        "--feature-y",
+        "--hosts=a",
+    ];
+    let cfg = Config::new()
+        .with_args(args)
+        .with_args_list("hosts")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert!(!cfg.feature_x);
+    assert!(cfg.feature_y);
+    assert_eq!(cfg.hosts, vec!["a"]);
+
+    // nested list key, given more than once
+    #[derive(Debug, Deserialize)]
+    struct Servers {
+        hosts: Vec<String>,
+    }
+
+    let cfg = Config::new()
+        .with_args_list("servers.hosts")
+        .with_args(["--servers.hosts=a", "--servers.hosts=b"])
+        .build_at::<Servers>("servers");
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_eq!(cfg.unwrap().hosts, vec!["a", "b"]);
+}
+
+// list key given once without declaring it
+#[test]
+fn test_args_list_once_failed() {
+    let args = [
+        "--database.host=localhost",
+        "--database.port=5432",
+        "--feature-x",
+        "--feature-y",
+        "--hosts=a",
+    ];
+    let cfg = Config::new().with_args(args).build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+}
+
+// arguments after `--` are not parsed
+#[test]
+fn test_args_end_of_options_success() {
+    #[derive(Debug, Deserialize)]
+    struct Cfg {
+        verbose: bool,
+        quiet: Option<bool>,
+    }
+
+    let args = ["program", "--verbose", "--", "--quiet"];
+    let cfg = Config::new().with_args(args).build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert!(cfg.verbose);
+    assert_eq!(cfg.quiet, None);
+}
+
+// arguments overwrite nested values from files without replacing the whole table
+#[test]
+fn test_args_overwrite_file_success() {
+    #[derive(Debug, Deserialize)]
+    struct SubEnv {
+        #[serde(rename = "subEnvString")]
+        sub_env_string: String,
+        #[serde(rename = "subEnvInteger")]
+        sub_env_integer: i64,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Cfg {
+        #[serde(rename = "envString")]
+        string: String,
+        #[serde(rename = "subEnv")]
+        sub_env: SubEnv,
+    }
+
+    let cfg = Config::new()
+        .with_args(["--subEnv.subEnvInteger=456", "--envString=from args"])
+        .with_json("./tests/data/json/test.json")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.string, "from args");
+    assert_eq!(cfg.sub_env.sub_env_string, "string");
+    assert_eq!(cfg.sub_env.sub_env_integer, 456);
+}
+
+// key set as both value and table
+#[test]
+fn test_args_conflicting_key_failed() {
+    let args = ["--database=postgres", "--database.port=5432"];
+    let cfg = Config::new().with_args(args).build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Command-line arguments error: invalid argument key: database.port"
+    );
+}
+
+// empty key segment
+#[test]
+fn test_args_invalid_key_failed() {
+    let cfg = Config::new()
+        .with_args(["--database..port=5432"])
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+}
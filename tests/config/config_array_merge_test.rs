@@ -0,0 +1,203 @@
+// test array merge strategies of sources
+
+use serde::Deserialize;
+
+use configrs::config::*;
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Plugin {
+    name: String,
+    enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Cfg {
+    allowed_origins: Vec<String>,
+    plugins: Vec<Plugin>,
+}
+
+const BASE: &str = "./tests/data/json/test_array_merge_base.json";
+const OVERLAY: &str = "./tests/data/json/test_array_merge_overlay.json";
+
+fn plugin(name: &str, enabled: bool) -> Plugin {
+    Plugin {
+        name: name.to_string(),
+        enabled,
+    }
+}
+
+fn layered() -> Config {
+    Config::new()
+        .with_json(BASE)
+        .with_layer(Layer::Defaults)
+        .with_json(OVERLAY)
+}
+
+// arrays of higher layer replace arrays of lower layer by default
+#[test]
+fn test_array_merge_replace_success() {
+    let cfg = layered().build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(
+        cfg.allowed_origins,
+        vec!["https://api.example.com", "http://localhost:3000"]
+    );
+    assert_eq!(
+        cfg.plugins,
+        vec![plugin("metrics", false), plugin("tracing", true)]
+    );
+}
+
+// global append strategy
+#[test]
+fn test_array_merge_append_success() {
+    let cfg = layered()
+        .with_array_merge(ArrayMerge::Append)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(
+        cfg.allowed_origins,
+        vec![
+            "https://example.com",
+            "https://api.example.com",
+            "https://api.example.com",
+            "http://localhost:3000"
+        ]
+    );
+    assert_eq!(cfg.plugins.len(), 4);
+}
+
+// per-key strategies, global strategy for other keys
+#[test]
+fn test_array_merge_per_key_success() {
+    let cfg = layered()
+        .with_array_merge(ArrayMerge::Prepend)
+        .with_array_merge_at("allowed_origins", ArrayMerge::Union)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(
+        cfg.allowed_origins,
+        vec![
+            "https://example.com",
+            "https://api.example.com",
+            "http://localhost:3000"
+        ]
+    );
+    assert_eq!(
+        cfg.plugins,
+        vec![
+            plugin("metrics", false),
+            plugin("tracing", true),
+            plugin("auth", true),
+            plugin("metrics", true)
+        ]
+    );
+}
+
+// per-key strategy at nested key path, set by a nested key path of a higher layer
+#[test]
+fn test_array_merge_nested_key_success() {
+    #[derive(Debug, Deserialize)]
+    struct Servers {
+        hosts: Vec<String>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Cfg {
+        servers: Servers,
+    }
+
+    let cfg = Config::new()
+        .with_json("./tests/data/json/test_array_merge_nested.json")
+        .with_args([
+            "--servers.hosts=c.example.com",
+            "--servers.hosts=d.example.com",
+        ])
+        .with_array_merge_at("servers.hosts", ArrayMerge::Append)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_eq!(
+        cfg.unwrap().servers.hosts,
+        vec![
+            "a.example.com",
+            "b.example.com",
+            "c.example.com",
+            "d.example.com"
+        ]
+    );
+}
+
+// arrays of tables merged by index
+#[test]
+fn test_array_merge_by_index_success() {
+    let cfg = layered()
+        .with_array_merge_at("plugins", ArrayMerge::ByIndex)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(
+        cfg.plugins,
+        vec![plugin("metrics", false), plugin("tracing", true)]
+    );
+}
+
+// arrays of tables merged by id field
+#[test]
+fn test_array_merge_by_id_success() {
+    let cfg = layered()
+        .with_array_merge_at("plugins", ArrayMerge::ById("name"))
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(
+        cfg.plugins,
+        vec![
+            plugin("auth", true),
+            plugin("metrics", false),
+            plugin("tracing", true)
+        ]
+    );
+}
+
+// arrays in the same layer are combined instead of duplicated key error
+#[test]
+fn test_array_merge_same_layer_success() {
+    let cfg = Config::new()
+        .with_value("allowed_origins", vec!["https://example.com"])
+        .with_value("allowed_origins", vec!["http://localhost:3000"])
+        .with_value("plugins", Vec::<Value>::new())
+        .with_array_merge(ArrayMerge::Append)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(
+        cfg.allowed_origins,
+        vec!["https://example.com", "http://localhost:3000"]
+    );
+}
+
+// arrays in the same layer with replace strategy are duplicated keys
+#[test]
+fn test_array_merge_same_layer_failed() {
+    let cfg = Config::new()
+        .with_json(BASE)
+        .with_json(OVERLAY)
+        .with_array_merge_at("plugins", ArrayMerge::Append)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Overwrite is false, found key: allowed_origins is duplicated"
+    );
+}
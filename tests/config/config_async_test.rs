@@ -0,0 +1,149 @@
+// test async sources and async build
+
+use serde::Deserialize;
+
+use configrs::config::*;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+// async source returning values after delay
+struct Delayed {
+    name: &'static str,
+    delay: Duration,
+    values: Vec<(&'static str, Value)>,
+}
+
+impl AsyncSource for Delayed {
+    fn name(&self) -> String {
+        self.name.to_string()
+    }
+
+    fn load(&self) -> BoxFuture<'_, Result<Value, Box<dyn std::error::Error + Send + Sync>>> {
+        Box::pin(async move {
+            tokio::time::sleep(self.delay).await;
+            let values = self
+                .values
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect::<HashMap<_, _>>();
+            Ok(Value::from(values))
+        })
+    }
+}
+
+struct Failing;
+
+impl AsyncSource for Failing {
+    fn load(&self) -> BoxFuture<'_, Result<Value, Box<dyn std::error::Error + Send + Sync>>> {
+        Box::pin(async move { Err("connection refused".into()) })
+    }
+}
+
+// async source returning a value that is not a table
+struct Scalar;
+
+impl AsyncSource for Scalar {
+    fn name(&self) -> String {
+        String::from("scalar")
+    }
+
+    fn load(&self) -> BoxFuture<'_, Result<Value, Box<dyn std::error::Error + Send + Sync>>> {
+        Box::pin(async move { Ok(Value::Int64(5)) })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Cfg {
+    host: String,
+    port: u16,
+    debug: bool,
+}
+
+fn delayed(name: &'static str, values: Vec<(&'static str, Value)>) -> Delayed {
+    Delayed {
+        name,
+        delay: Duration::from_millis(300),
+        values,
+    }
+}
+
+// sync and async sources in one precedence chain, async sources loaded concurrently
+#[tokio::test]
+async fn test_build_async_success() {
+    let config = Config::empty()
+        .with_json("./tests/data/json/test_layer_defaults.json")
+        .with_layer(Layer::Defaults)
+        .with_async_source(delayed("remote", vec![("port", Value::from(9090))]))
+        .with_async_source(delayed("flags", vec![("debug", Value::from(true))]))
+        .with_layer(Layer::Env)
+        .with_args(["--host=app.example.com"]);
+
+    let sources = config.sources();
+    assert_eq!(sources[1].kind, SourceKind::Async(String::from("remote")));
+    assert_eq!(sources[1].layer, Layer::File);
+    assert_eq!(sources[2].layer, Layer::Env);
+
+    let start = Instant::now();
+    let cfg = config.build_async::<Cfg>().await;
+    let elapsed = start.elapsed();
+    dbg!(&cfg, elapsed);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.host, "app.example.com");
+    assert_eq!(cfg.port, 9090);
+    assert!(cfg.debug);
+    assert!(elapsed < Duration::from_millis(550));
+}
+
+// async sources conflicting in the same layer
+#[tokio::test]
+async fn test_build_async_conflict_failed() {
+    let cfg = Config::empty()
+        .with_json("./tests/data/json/test_layer_defaults.json")
+        .with_async_source(delayed("remote", vec![("port", Value::from(9090))]))
+        .with_value("debug", false)
+        .build_async::<Cfg>()
+        .await;
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Overwrite is false, found key: port is duplicated"
+    );
+}
+
+// failing async source, async source of a non-table value, and async source with sync build
+#[tokio::test]
+async fn test_build_async_failed() {
+    let cfg = Config::empty()
+        .with_async_source(Failing)
+        .build_async::<Cfg>()
+        .await;
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Source error: async: connection refused"
+    );
+
+    let cfg = Config::empty()
+        .with_async_source(Scalar)
+        .build_async::<Cfg>()
+        .await;
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Source error: scalar: expected a table of keys"
+    );
+
+    let cfg = Config::empty()
+        .with_async_source(delayed("remote", vec![]))
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Failed building config: async source remote requires build_async"
+    );
+}
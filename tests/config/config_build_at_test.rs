@@ -0,0 +1,68 @@
+// test building subtree at key path
+
+use serde::Deserialize;
+
+use configrs::config::*;
+
+const FILE: &str = "./tests/data/yaml/test_build_at.yaml";
+
+#[derive(Debug, Deserialize)]
+struct Service {
+    url: String,
+    retries: u32,
+}
+
+// build nested subtree, and non-table values
+#[test]
+fn test_build_at_success() {
+    let config = Config::new()
+        .with_yaml(FILE)
+        .with_args(["--services.billing.retries=5"]);
+
+    let billing = config.build_at::<Service>("services.billing");
+    dbg!(&billing);
+    assert!(billing.is_ok());
+    let billing = billing.unwrap();
+    assert_eq!(billing.url, "https://billing.example.com");
+    assert_eq!(billing.retries, 5);
+
+    let mailer = config.build_at::<Service>("services.mailer");
+    dbg!(&mailer);
+    assert!(mailer.is_ok());
+    assert_eq!(mailer.unwrap().retries, 1);
+
+    let ports = config.build_at::<Vec<u16>>("ports");
+    dbg!(&ports);
+    assert!(ports.is_ok());
+    assert_eq!(ports.unwrap(), vec![80, 443]);
+}
+
+// key path with normalized keys
+#[test]
+fn test_build_at_normalized_success() {
+    let billing = Config::new()
+        .with_yaml(FILE)
+        .with_env_prefix("SERVICES_")
+        .with_normalized_keys()
+        .build_at::<Service>("Services.Billing");
+    dbg!(&billing);
+    assert!(billing.is_ok());
+}
+
+// missing key path
+#[test]
+fn test_build_at_failed() {
+    let config = Config::new().with_yaml(FILE);
+
+    let cfg = config.build_at::<Service>("services.payments");
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Failed building config: key path not found: services.payments"
+    );
+
+    let cfg = config.build_at::<Service>("ports.url");
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+}
@@ -0,0 +1,109 @@
+// test configs from clap arguments
+
+use clap::{Arg, ArgAction, Command, CommandFactory, FromArgMatches, Parser};
+use serde::Deserialize;
+
+use configrs::config::*;
+
+#[derive(Debug, Parser)]
+struct Cli {
+    #[arg(long = "port", id = "database.port", default_value_t = 5432)]
+    port: u16,
+    #[arg(long = "host", id = "database.host", default_value = "localhost")]
+    host: String,
+    #[arg(long)]
+    verbose: bool,
+    #[arg(long = "origin")]
+    origins: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Database {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+struct Cfg {
+    database: Database,
+    verbose: Option<bool>,
+    origins: Option<Vec<String>>,
+}
+
+// explicitly supplied arguments overwrite file values, defaults don't
+#[test]
+fn test_clap_derive_success() {
+    let command = Cli::command();
+    let matches = command
+        .clone()
+        .get_matches_from(["program", "--port", "6543", "--origin", "a.com"]);
+    let cli = Cli::from_arg_matches(&matches).unwrap();
+    assert_eq!(cli.host, "localhost");
+
+    let cfg = Config::new()
+        .with_json("./tests/data/json/test_clap.json")
+        .with_clap(&command, &matches)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.database.host, "db.example.com");
+    assert_eq!(cfg.database.port, 6543);
+    assert_eq!(cfg.verbose, None);
+    assert_eq!(cfg.origins, Some(vec!["a.com".to_string()]));
+}
+
+// default values only don't shadow anything
+#[test]
+fn test_clap_defaults_not_added_success() {
+    let command = Cli::command();
+    let matches = command.clone().get_matches_from(["program"]);
+
+    let cfg = Config::new()
+        .with_json("./tests/data/json/test_clap.json")
+        .with_clap(&command, &matches)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.database.host, "db.example.com");
+    assert_eq!(cfg.database.port, 3306);
+    assert_eq!(cfg.verbose, None);
+    assert_eq!(cfg.origins, None);
+}
+
+// builder arguments, counts, flags and subcommands
+#[test]
+fn test_clap_builder_subcommand_success() {
+    #[derive(Debug, Deserialize)]
+    struct Run {
+        fast: bool,
+        jobs: u32,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Cfg {
+        verbosity: u8,
+        run: Run,
+    }
+
+    let command = Command::new("program")
+        .arg(Arg::new("verbosity").short('v').action(ArgAction::Count))
+        .subcommand(
+            Command::new("run")
+                .arg(Arg::new("fast").long("fast").action(ArgAction::SetTrue))
+                .arg(Arg::new("jobs").long("jobs")),
+        );
+    let matches =
+        command
+            .clone()
+            .get_matches_from(["program", "-vv", "run", "--fast", "--jobs", "4"]);
+
+    let cfg = Config::new().with_clap(&command, &matches).build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.verbosity, 2);
+    assert!(cfg.run.fast);
+    assert_eq!(cfg.run.jobs, 4);
+}
@@ -0,0 +1,146 @@
+// test configurations from Consul KV
+
+use serde::Deserialize;
+
+use configrs::config::*;
+use std::time::{Duration, Instant};
+
+use super::http_stub::{Stub, header, response, unreachable_url};
+
+// keys under `app/config`, with a folder, and a key of another prefix sharing its beginning
+const KV: &str = r#"[
+    { "Key": "app/config/", "Value": null },
+    { "Key": "app/config/db/host", "Value": "ZGIuZXhhbXBsZS5jb20=" },
+    { "Key": "app/config/db/port", "Value": "NTQzMg==" },
+    { "Key": "app/config/debug", "Value": "dHJ1ZQ==" },
+    { "Key": "app/config/ratio", "Value": "MC41" }
+]"#;
+
+#[derive(Debug, Deserialize)]
+struct Db {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+struct Cfg {
+    db: Db,
+    debug: bool,
+    ratio: f64,
+}
+
+// read keys under prefix into nested values
+#[test]
+fn test_consul_success() {
+    let stub = Stub::serve(|request| match header(request, "x-consul-token") {
+        Some("secret") => response("200 OK", &[("X-Consul-Index", "7")], KV),
+        _ => response("403 Forbidden", &[], ""),
+    });
+
+    let cfg = Config::empty()
+        .with_consul(&stub.url, "/app/config/")
+        .with_header("X-Consul-Token", "secret")
+        .with_args(["--db.port=6432"])
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.db.host, "db.example.com");
+    assert_eq!(cfg.db.port, 6432);
+    assert!(cfg.debug);
+    assert_eq!(cfg.ratio, 0.5);
+    assert!(stub.requests()[0].starts_with("GET /v1/kv/app/config/?recurse=true"));
+}
+
+// prefix without keys is empty
+#[test]
+fn test_consul_not_found_success() {
+    let stub = Stub::serve(|_| response("404 Not Found", &[("X-Consul-Index", "7")], ""));
+
+    #[derive(Debug, Deserialize)]
+    struct Cfg {
+        debug: Option<bool>,
+    }
+
+    let cfg = Config::empty().with_consul(&stub.url, "app").build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_eq!(cfg.unwrap().debug, None);
+}
+
+// denied request, invalid value, unreachable agent
+#[test]
+fn test_consul_failed() {
+    let stub = Stub::serve(|request| match header(request, "x-consul-token") {
+        Some(_) => response(
+            "200 OK",
+            &[],
+            r#"[{ "Key": "app/db/host", "Value": "//4=" }]"#,
+        ),
+        None => response("403 Forbidden", &[], ""),
+    });
+
+    let cfg = Config::empty().with_consul(&stub.url, "app").build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+
+    let cfg = Config::empty()
+        .with_consul(&stub.url, "app")
+        .with_header("X-Consul-Token", "secret")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Consul error: invalid value of key app/db/host"
+    );
+
+    let cfg = Config::empty()
+        .with_consul(&unreachable_url(), "app")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+}
+
+// blocking queries return once the index changes
+#[test]
+fn test_consul_watch_success() {
+    let stub = Stub::serve(|request| {
+        if request.contains("index=7") {
+            std::thread::sleep(Duration::from_millis(200));
+            let kv = KV.replace("NTQzMg==", "NjQzMg==");
+            response("200 OK", &[("X-Consul-Index", "8")], &kv)
+        } else if request.contains("index=8") {
+            std::thread::sleep(Duration::from_millis(200));
+            response("200 OK", &[("X-Consul-Index", "8")], KV)
+        } else {
+            response("200 OK", &[("X-Consul-Index", "7")], KV)
+        }
+    });
+    let config = Config::empty().with_consul(&stub.url, "app/config");
+
+    let index = config.watch_consul(0, Duration::from_secs(1));
+    dbg!(&index);
+    assert_eq!(index.unwrap(), 7);
+    assert_eq!(config.build::<Cfg>().unwrap().db.port, 5432);
+
+    let now = Instant::now();
+    let index = config.watch_consul(7, Duration::from_secs(1));
+    dbg!(&index);
+    assert_eq!(index.unwrap(), 8);
+    assert!(now.elapsed() >= Duration::from_millis(200));
+    assert!(stub.requests()[2].contains("&index=7&wait=1000ms"));
+
+    // unchanged when wait has elapsed
+    let index = config.watch_consul(8, Duration::from_secs(1));
+    dbg!(&index);
+    assert_eq!(index.unwrap(), 8);
+}
+
+// watching without consul sources
+#[test]
+fn test_consul_watch_failed() {
+    let index = Config::empty().watch_consul(0, Duration::from_secs(1));
+    dbg!(&index);
+    assert!(index.is_err());
+}
@@ -0,0 +1,148 @@
+// test encrypted values decrypted with a local key
+
+use serde::Deserialize;
+
+use configrs::config::*;
+use std::env;
+use std::sync::Mutex;
+
+// TEST_MUTEX will make sure tests are run synchronized, for env vars access and modify.
+// will be poisoned if one of the tests is panicked.
+static TEST_MUTEX: Mutex<()> = Mutex::new(());
+
+const FILE: &str = "./tests/data/toml/test_encrypted.toml";
+const KEY: &str = "./tests/data/key/test.key";
+
+#[derive(Debug, Deserialize)]
+struct Tls {
+    enabled: bool,
+    tokens: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Cfg {
+    host: String,
+    password: Secret<String>,
+    port: u16,
+    tls: Tls,
+}
+
+// decrypt values with key from file, into their types
+#[test]
+fn test_encryption_key_file_success() {
+    let cfg = Config::empty()
+        .with_toml(FILE)
+        .with_decryption_key_file(KEY)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.host, "db.example.com");
+    assert_eq!(cfg.password.expose(), "hunter2");
+    assert_eq!(cfg.port, 5432);
+    assert!(cfg.tls.enabled);
+    assert_eq!(cfg.tls.tokens, vec!["token-1", "token-2"]);
+}
+
+// decrypt values with key from env var, values encrypted by encrypt_value from any source
+#[test]
+fn test_encryption_key_env_success() {
+    let _lock = TEST_MUTEX.lock().unwrap();
+
+    let key = generate_key();
+    unsafe {
+        env::set_var("CONFIGRS_TEST_KEY", &key);
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Cfg {
+        password: String,
+        port: i64,
+        ratio: f64,
+        debug: bool,
+    }
+
+    let cfg = Config::empty()
+        .with_value("password", encrypt_value(&key, "hunter2").unwrap())
+        .with_value("port", encrypt_value(&key, 5432).unwrap())
+        .with_value("ratio", encrypt_value(&key, 0.5).unwrap())
+        .with_args([format!("--debug={}", encrypt_value(&key, true).unwrap())])
+        .with_decryption_key_env("CONFIGRS_TEST_KEY")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.password, "hunter2");
+    assert_eq!(cfg.port, 5432);
+    assert_eq!(cfg.ratio, 0.5);
+    assert!(cfg.debug);
+
+    unsafe {
+        env::remove_var("CONFIGRS_TEST_KEY");
+    }
+}
+
+#[test]
+fn test_encryption_key_env_vars_success() {
+    let key = generate_key();
+
+    #[derive(Debug, Deserialize)]
+    struct Cfg {
+        password: String,
+    }
+
+    let cfg = Config::empty()
+        .with_env_vars([("CONFIGRS_TEST_INJECTED_KEY", key.as_str())])
+        .with_value("password", encrypt_value(&key, "hunter2").unwrap())
+        .with_decryption_key_env("CONFIGRS_TEST_INJECTED_KEY")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_eq!(cfg.unwrap().password, "hunter2");
+}
+
+// wrong key, tampered value, invalid or missing key
+#[test]
+fn test_encryption_failed() {
+    let cfg = Config::empty()
+        .with_toml(FILE)
+        .with_decryption_key_file("./tests/data/key/test_wrong.key")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(cfg.unwrap_err().key_path(), Some("password"));
+
+    let cfg = Config::empty()
+        .with_toml("./tests/data/toml/test_encrypted_tampered.toml")
+        .with_decryption_key_file(KEY)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    let err = cfg.unwrap_err();
+    assert_eq!(err.key_path(), Some("database.password"));
+    assert_eq!(
+        err.to_string(),
+        "[CONFIG][ERROR] Decryption error at database.password: wrong key or tampered value"
+    );
+
+    let cfg = Config::empty()
+        .with_toml(FILE)
+        .with_decryption_key_file(FILE)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(cfg.unwrap_err().key_path(), None);
+
+    let cfg = Config::empty()
+        .with_toml(FILE)
+        .with_decryption_key_env("CONFIGRS_TEST_KEY_NOT_FOUND")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+
+    let err = encrypt_value("invalid", "hunter2").unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Encryption error: key must be 32 bytes")
+    );
+}
@@ -0,0 +1,200 @@
+// test parsing env vars into arrays and maps
+
+use serde::Deserialize;
+
+use configrs::config::*;
+use std::collections::HashMap;
+use std::env;
+use std::sync::Mutex;
+
+// TEST_MUTEX will make sure tests are run synchronized, for env vars access and modify.
+// will be poisoned if one of the tests is panicked.
+static TEST_MUTEX: Mutex<()> = Mutex::new(());
+
+const FILE: &str = "./tests/data/env/test_parse.env";
+
+#[derive(Debug, Deserialize)]
+struct Cfg {
+    #[serde(alias = "HOSTS")]
+    hosts: Vec<String>,
+    #[serde(alias = "PORTS")]
+    ports: Vec<String>,
+    #[serde(alias = "LABELS")]
+    labels: HashMap<String, String>,
+    #[serde(alias = "NODE")]
+    nodes: Vec<String>,
+}
+
+// lists, maps and indexed keys from .env file
+#[test]
+fn test_env_parse_success() {
+    let cfg = Config::new()
+        .with_env(FILE)
+        .with_env_list("HOSTS")
+        .with_env_map("LABELS")
+        .with_env_indexed("NODE")
+        .with_env_list("PORTS")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(
+        cfg.hosts,
+        vec!["a.example.com", "b.example.com", "c.example.com"]
+    );
+    assert_eq!(cfg.ports, vec!["80;443"]);
+    assert_eq!(cfg.labels.len(), 2);
+    assert_eq!(cfg.labels["team"], "core");
+    assert_eq!(cfg.labels["tier"], "1");
+    assert_eq!(cfg.nodes, vec!["10.0.0.1", "10.0.0.2", "10.0.0.3"]);
+}
+
+// custom separator
+#[test]
+fn test_env_parse_separator_success() {
+    #[derive(Debug, Deserialize)]
+    struct Ports {
+        #[serde(alias = "PORTS")]
+        ports: Vec<String>,
+    }
+
+    let cfg = Config::new()
+        .with_env(FILE)
+        .with_env_list("PORTS")
+        .with_env_separator(';')
+        .build::<Ports>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_eq!(cfg.unwrap().ports, vec!["80", "443"]);
+}
+
+// quoted items from env vars, with prefix
+#[test]
+fn test_env_parse_env_vars_success() {
+    let _lock = TEST_MUTEX.lock().unwrap();
+
+    unsafe {
+        env::set_var("PARSE_ORIGINS", r#""https://a.example.com,x", 'c d' ,, e"#);
+    }
+    unsafe {
+        env::set_var("PARSE_EMPTY", "");
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Origins {
+        #[serde(alias = "PARSE_ORIGINS")]
+        origins: Vec<String>,
+        #[serde(alias = "PARSE_EMPTY")]
+        empty: Vec<String>,
+    }
+
+    let cfg = Config::new()
+        .with_env_list("PARSE_ORIGINS")
+        .with_env_list("PARSE_EMPTY")
+        .with_env_prefix("PARSE_")
+        .build::<Origins>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.origins, vec!["https://a.example.com,x", "c d", "", "e"]);
+    assert!(cfg.empty.is_empty());
+
+    unsafe {
+        env::set_var("PARSE_ORIGINS", r#""https://a.example.com"#);
+    }
+
+    let cfg = Config::new()
+        .with_env_list("PARSE_ORIGINS")
+        .with_env_prefix("PARSE_")
+        .build::<Origins>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Env parsing error: unterminated quote in PARSE_ORIGINS"
+    );
+
+    unsafe {
+        env::remove_var("PARSE_ORIGINS");
+    }
+    unsafe {
+        env::remove_var("PARSE_EMPTY");
+    }
+}
+
+// map keys and values trimmed like list items, and trailing separator dropped
+#[test]
+fn test_env_parse_trim_success() {
+    #[derive(Debug, Deserialize)]
+    struct Trim {
+        hosts: Vec<String>,
+        labels: HashMap<String, String>,
+    }
+
+    let cfg = Config::empty()
+        .with_env_vars([
+            ("hosts", "a.example.com, b.example.com ,"),
+            ("labels", r#"team = core , tier= 1, motd=' hi = there ',"#),
+        ])
+        .with_env_list("hosts")
+        .with_env_map("labels")
+        .build::<Trim>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.hosts, vec!["a.example.com", "b.example.com"]);
+    assert_eq!(cfg.labels.len(), 3);
+    assert_eq!(cfg.labels["team"], "core");
+    assert_eq!(cfg.labels["tier"], "1");
+    assert_eq!(cfg.labels["motd"], " hi = there ");
+}
+
+// invalid map item and indexed key set directly
+#[test]
+fn test_env_parse_failed() {
+    let cfg = Config::new()
+        .with_env(FILE)
+        .with_env_map("HOSTS")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Env parsing error: expected `key=value` in HOSTS, found: a.example.com"
+    );
+
+    let cfg = Config::new()
+        .with_env(FILE)
+        .with_env_indexed("NODE")
+        .with_value("NODE", "10.0.0.4")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Overwrite is false, found key: NODE is duplicated"
+    );
+
+    // sparse and duplicated indexes
+    let cfg = Config::empty()
+        .with_env_vars([("NODE_0", "10.0.0.1"), ("NODE_5", "10.0.0.6")])
+        .with_env_indexed("NODE")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Env parsing error: missing index 1 of NODE, found: NODE_5"
+    );
+
+    let cfg = Config::empty()
+        .with_env_vars([("NODE_0", "10.0.0.1"), ("NODE_00", "10.0.0.2")])
+        .with_env_indexed("NODE")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Env parsing error: duplicated index 0 of NODE"
+    );
+}
@@ -1397,3 +1397,70 @@ fn test_env_vars_nested_default_custom_success() {
         env::remove_var("ENV_ARR_NESTED_NESTED");
     }
 }
+
+// load config from injected env vars, without OS env vars
+#[test]
+fn test_injected_env_vars_success() {
+    #[derive(Debug, Deserialize)]
+    struct Cfg {
+        #[serde(alias = "APP_HOSTS")]
+        hosts: Vec<String>,
+        #[serde(alias = "APP_PORT")]
+        port: u16,
+        #[serde(alias = "APP_DEBUG")]
+        debug: bool,
+        #[serde(alias = "PATH")]
+        path: Option<String>,
+    }
+
+    let vars = vec![
+        ("APP_HOSTS", "a.example.com,b.example.com"),
+        ("APP_PORT", "8080"),
+        ("APP_DEBUG", "true"),
+    ];
+    let cfg = Config::empty()
+        .with_env_vars(vars)
+        .with_env_list("APP_HOSTS")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.hosts, vec!["a.example.com", "b.example.com"]);
+    assert_eq!(cfg.port, 8080);
+    assert!(cfg.debug);
+    assert_eq!(cfg.path, None);
+}
+
+// injected env vars are merged like env vars of OS
+#[test]
+fn test_injected_env_vars_failed() {
+    #[derive(Debug, Deserialize)]
+    struct Cfg {
+        #[serde(alias = "APP_PORT")]
+        port: u16,
+    }
+
+    let cfg = Config::empty().build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+
+    let cfg = Config::empty()
+        .with_env_vars([("APP_PORT", "8080")])
+        .with_env_vars([("APP_PORT", "9090")])
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Overwrite is false, found key: APP_PORT is duplicated"
+    );
+
+    let cfg = Config::empty()
+        .with_env_vars([("APP_PORT", "8080")])
+        .with_env_vars([("APP_PORT", "9090")])
+        .with_conflict(Conflict::LastWins)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_eq!(cfg.unwrap().port, 9090);
+}
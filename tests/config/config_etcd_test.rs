@@ -0,0 +1,160 @@
+// test configurations from etcd v3 gateway
+
+use serde::Deserialize;
+
+use configrs::config::*;
+use std::time::{Duration, Instant};
+
+use super::http_stub::{Stub, response, unreachable_url};
+
+// keys `/app/db/host`, `/app/db/port` and `/app/debug`
+const RANGE: &str = r#"{
+    "header": { "revision": "12" },
+    "kvs": [
+        { "key": "L2FwcC9kYi9ob3N0", "value": "ZGIuZXhhbXBsZS5jb20=", "mod_revision": "10" },
+        { "key": "L2FwcC9kYi9wb3J0", "value": "NTQzMg==", "mod_revision": "11" },
+        { "key": "L2FwcC9kZWJ1Zw==", "value": "dHJ1ZQ==", "mod_revision": "12" }
+    ],
+    "count": "3"
+}"#;
+
+const CREATED: &str = r#"{"result":{"header":{"revision":"12"},"created":true}}"#;
+
+#[derive(Debug, Deserialize)]
+struct Db {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+struct Cfg {
+    db: Db,
+    debug: bool,
+}
+
+// range request of keys under prefix into nested values
+#[test]
+fn test_etcd_success() {
+    let stub = Stub::serve(|request| {
+        if request.starts_with("POST /v3/kv/range")
+            && request.contains(r#""key":"L2FwcC8=""#)
+            && request.contains(r#""range_end":"L2FwcDA=""#)
+        {
+            response("200 OK", &[], RANGE)
+        } else {
+            response("404 Not Found", &[], "")
+        }
+    });
+
+    let cfg = Config::empty()
+        .with_etcd(&stub.url, "/app")
+        .with_value("debug", false)
+        .with_layer(Layer::Override)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.db.host, "db.example.com");
+    assert_eq!(cfg.db.port, 5432);
+    assert!(!cfg.debug);
+}
+
+// empty prefix
+#[test]
+fn test_etcd_empty_success() {
+    let stub = Stub::serve(|_| response("200 OK", &[], r#"{ "header": { "revision": "3" } }"#));
+
+    #[derive(Debug, Deserialize)]
+    struct Cfg {
+        debug: Option<bool>,
+    }
+
+    let cfg = Config::empty().with_etcd(&stub.url, "/app").build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_eq!(cfg.unwrap().debug, None);
+}
+
+// invalid response, unreachable gateway
+#[test]
+fn test_etcd_failed() {
+    let stub = Stub::serve(|_| response("200 OK", &[], r#"{ "kvs": [] }"#));
+
+    let cfg = Config::empty().with_etcd(&stub.url, "/app").build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+
+    let cfg = Config::empty()
+        .with_etcd(&unreachable_url(), "/app")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+}
+
+// watch returns the revision of the first change
+#[test]
+fn test_etcd_watch_success() {
+    let stub = Stub::serve(|request| {
+        if request.starts_with("POST /v3/watch") {
+            std::thread::sleep(Duration::from_millis(200));
+            let event = r#"{"result":{"header":{"revision":"13"},"events":[{"kv":{"key":"L2FwcC9kYi9wb3J0","value":"NjQzMg==","mod_revision":"13"}}]}}"#;
+            response("200 OK", &[], &format!("{}\n{}\n", CREATED, event))
+        } else {
+            response("200 OK", &[], RANGE)
+        }
+    });
+    let config = Config::empty().with_etcd(&stub.url, "/app/");
+
+    let revision = config.watch_etcd(0, Duration::from_secs(1));
+    dbg!(&revision);
+    assert_eq!(revision.unwrap(), 12);
+
+    let now = Instant::now();
+    let revision = config.watch_etcd(12, Duration::from_secs(1));
+    dbg!(&revision);
+    assert_eq!(revision.unwrap(), 13);
+    assert!(now.elapsed() >= Duration::from_millis(200));
+    assert!(stub.requests()[1].contains(r#""start_revision":"13""#));
+}
+
+// watch returns the same revision when wait has elapsed without changes
+#[test]
+fn test_etcd_watch_unchanged_success() {
+    let stub = Stub::serve(|_| {
+        std::thread::sleep(Duration::from_millis(500));
+        response("200 OK", &[], CREATED)
+    });
+    let config = Config::empty().with_etcd(&stub.url, "/app");
+
+    let revision = config.watch_etcd(12, Duration::from_millis(200));
+    dbg!(&revision);
+    assert_eq!(revision.unwrap(), 12);
+
+    let stub = Stub::serve(|_| response("200 OK", &[], CREATED));
+    let config = Config::empty().with_etcd(&stub.url, "/app");
+
+    let revision = config.watch_etcd(12, Duration::from_secs(1));
+    dbg!(&revision);
+    assert_eq!(revision.unwrap(), 12);
+}
+
+// watch errors and watching without etcd sources
+#[test]
+fn test_etcd_watch_failed() {
+    let stub = Stub::serve(|_| {
+        response(
+            "200 OK",
+            &[],
+            r#"{"error":{"grpc_code":9,"message":"permission denied"}}"#,
+        )
+    });
+    let revision = Config::empty()
+        .with_etcd(&stub.url, "/app")
+        .watch_etcd(12, Duration::from_secs(1));
+    dbg!(&revision);
+    assert!(revision.is_err());
+
+    let revision = Config::empty().watch_etcd(0, Duration::from_secs(1));
+    dbg!(&revision);
+    assert!(revision.is_err());
+}
@@ -0,0 +1,92 @@
+// test file for loading configuration from json5 file
+
+use serde::{Deserialize, Serialize};
+
+use configrs::config::*;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Env {
+    #[serde(alias = "envString")]
+    pub string: String,
+    #[serde(alias = "envInteger")]
+    pub integer: i64,
+    #[serde(alias = "envFloat")]
+    pub float: f64,
+    #[serde(alias = "envBoolean")]
+    pub boolean: bool,
+    #[serde(alias = "envArr")]
+    pub arr: Vec<String>,
+    #[serde(alias = "subEnv")]
+    pub sub_env: SubEnv,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubEnv {
+    #[serde(alias = "subEnvString")]
+    pub sub_env_string: String,
+    #[serde(alias = "subEnvInteger")]
+    pub sub_env_integer: i64,
+    #[serde(alias = "subEnvFloat")]
+    pub sub_env_float: f64,
+    #[serde(alias = "subEnvBoolean")]
+    pub sub_env_boolean: bool,
+    #[serde(alias = "subEnvArr")]
+    pub sub_env_arr: Vec<String>,
+}
+
+// load json5 with comments, trailing commas, unquoted keys and single-quoted strings
+#[test]
+fn test_json5_success() {
+    let file_path = "./tests/data/json5/test.json5";
+
+    let cfg = Config::new().with_json5(file_path).build::<Env>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.string, "string");
+    assert_eq!(cfg.integer, 123);
+    assert_eq!(cfg.float, 123.0);
+    assert!(cfg.boolean);
+    assert_eq!(cfg.arr, vec!["anu", "nganu", "lskmdf", "lwkef", "lkemrg"]);
+    assert_eq!(cfg.sub_env.sub_env_string, "string");
+    assert_eq!(cfg.sub_env.sub_env_integer, 123);
+    assert_eq!(cfg.sub_env.sub_env_float, 123.0);
+    assert!(cfg.sub_env.sub_env_boolean);
+    assert_eq!(
+        cfg.sub_env.sub_env_arr,
+        vec!["anu", "nganu", "lskmdf", "lwkef", "lkemrg"]
+    );
+}
+
+// invalid json5 is reported with line and column
+#[test]
+fn test_json5_invalid_failed() {
+    let file_path = "./tests/data/json5/test_invalid.json5";
+
+    let cfg = Config::new().with_json5(file_path).build::<Env>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    let err = cfg.unwrap_err().to_string();
+    assert!(err.starts_with("[CONFIG][ERROR] Json5 parsing error: line 3, column 17:"));
+}
+
+// same keys in json and json5 without overwrite
+#[test]
+fn test_json5_duplicate_with_json_failed() {
+    let cfg = Config::new()
+        .with_json("./tests/data/json/test.json")
+        .with_json5("./tests/data/json5/test.json5")
+        .build::<Env>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+}
+
+// file not found
+#[test]
+fn test_json5_file_not_found_failed() {
+    let cfg = Config::new()
+        .with_json5("./tests/data/json5/not_found.json5")
+        .build::<Env>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+}
@@ -0,0 +1,205 @@
+// test layered precedence and conflict policies of sources
+
+use serde::Deserialize;
+
+use configrs::config::*;
+
+#[derive(Debug, Deserialize)]
+struct Database {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+struct Cfg {
+    host: String,
+    port: u16,
+    database: Database,
+}
+
+const DEFAULTS: &str = "./tests/data/json/test_layer_defaults.json";
+const FILE: &str = "./tests/data/json/test_layer_file.json";
+
+// defaults layer is overwritten by file layer, without overwrite
+#[test]
+fn test_layer_defaults_file_success() {
+    let cfg = Config::new()
+        .with_json(FILE)
+        .with_json(DEFAULTS)
+        .with_layer(Layer::Defaults)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.host, "app.example.com");
+    assert_eq!(cfg.port, 8080);
+    assert_eq!(cfg.database.host, "db.example.com");
+    assert_eq!(cfg.database.port, 6543);
+}
+
+// env layer overwrites file layer, whatever the declaration order
+#[test]
+fn test_layer_env_overwrites_file_success() {
+    let cfg = Config::new()
+        .with_value("port", 9090)
+        .with_json(DEFAULTS)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.host, "localhost");
+    assert_eq!(cfg.port, 9090);
+}
+
+// env vars overwrite keys of files without being duplicates, with or without overwrite
+#[test]
+fn test_layer_env_vars_file_success() {
+    let cfg = Config::empty()
+        .with_env_vars([("host", "env.example.com")])
+        .with_json(FILE)
+        .with_json(DEFAULTS)
+        .with_layer(Layer::Defaults)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_eq!(cfg.unwrap().host, "env.example.com");
+
+    let cfg = Config::empty()
+        .with_env_vars([("host", "env.example.com")])
+        .with_overwrite()
+        .with_json(FILE)
+        .with_json(DEFAULTS)
+        .with_layer(Layer::Defaults)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_eq!(cfg.unwrap().host, "env.example.com");
+
+    // files win over env vars moved to a lower layer
+    let cfg = Config::empty()
+        .with_env_vars([("host", "env.example.com")])
+        .with_layer(Layer::Defaults)
+        .with_json(FILE)
+        .with_json(DEFAULTS)
+        .with_layer(Layer::Defaults)
+        .with_conflict(Conflict::FirstWins)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_eq!(cfg.unwrap().host, "app.example.com");
+}
+
+// override layer overwrites nested key paths of every other layer
+#[test]
+fn test_layer_override_success() {
+    let cfg = Config::new()
+        .with_args(["--database.port=7000"])
+        .with_value("port", 9090)
+        .with_json(DEFAULTS)
+        .with_layer(Layer::Defaults)
+        .with_json(FILE)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.host, "app.example.com");
+    assert_eq!(cfg.port, 9090);
+    assert_eq!(cfg.database.host, "db.example.com");
+    assert_eq!(cfg.database.port, 7000);
+}
+
+// value moved into override layer
+#[test]
+fn test_layer_value_override_success() {
+    let cfg = Config::new()
+        .with_value("host", "from value")
+        .with_layer(Layer::Override)
+        .with_json(DEFAULTS)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_eq!(cfg.unwrap().host, "from value");
+}
+
+// same key in the same layer errors by default
+#[test]
+fn test_conflict_error_failed() {
+    let cfg = Config::new()
+        .with_json(DEFAULTS)
+        .with_json(FILE)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Overwrite is false, found key: database is duplicated"
+    );
+}
+
+// same nested key in the same layer
+#[test]
+fn test_conflict_error_nested_failed() {
+    let cfg = Config::new()
+        .with_args(["--database.port=7000"])
+        .with_args(["--database.port=7001", "--database.host=other"])
+        .with_json(DEFAULTS)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Overwrite is false, found key: database.port is duplicated"
+    );
+}
+
+// first declared source wins
+#[test]
+fn test_conflict_first_wins_success() {
+    let cfg = Config::new()
+        .with_json(DEFAULTS)
+        .with_json(FILE)
+        .with_conflict(Conflict::FirstWins)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.host, "localhost");
+    assert_eq!(cfg.port, 8080);
+    assert_eq!(cfg.database.host, "localhost");
+    assert_eq!(cfg.database.port, 5432);
+}
+
+// last declared source wins
+#[test]
+fn test_conflict_last_wins_success() {
+    let cfg = Config::new()
+        .with_json(DEFAULTS)
+        .with_json(FILE)
+        .with_conflict(Conflict::LastWins)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.host, "app.example.com");
+    assert_eq!(cfg.port, 8080);
+    assert_eq!(cfg.database.host, "db.example.com");
+    assert_eq!(cfg.database.port, 6543);
+}
+
+// conflict policy applies to the last declared source only
+#[test]
+fn test_conflict_policy_per_source_failed() {
+    let cfg = Config::new()
+        .with_json(DEFAULTS)
+        .with_json(FILE)
+        .with_conflict(Conflict::LastWins)
+        .with_value("port", 1)
+        .with_value("port", 2)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Overwrite is false, found key: port is duplicated"
+    );
+}
@@ -0,0 +1,136 @@
+// test sources declared by the builder and read on build
+
+use serde::Deserialize;
+
+use configrs::config::*;
+use std::env;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+
+// TEST_MUTEX will make sure tests are run synchronized, for env vars access and modify.
+// will be poisoned if one of the tests is panicked.
+static TEST_MUTEX: Mutex<()> = Mutex::new(());
+
+const FILE: &str = "./tests/data/json/test_layer_file.json";
+
+static CONFIG: LazyLock<Config> = LazyLock::new(|| {
+    Config::empty()
+        .with_json("./tests/data/json/test_layer_defaults.json")
+        .with_layer(Layer::Defaults)
+        .with_json(FILE)
+});
+
+#[derive(Debug, Deserialize)]
+struct Cfg {
+    host: String,
+    port: u16,
+}
+
+// sources are described without being read
+#[test]
+fn test_lazy_sources_success() {
+    let config = Config::new()
+        .with_json("./tests/data/json/not_found.json")
+        .with_layer(Layer::Defaults)
+        .with_value("port", 8080)
+        .with_overwrite()
+        .with_yaml("./tests/data/yaml/not_found.yaml")
+        .with_args(["--port=9090"])
+        .without_key("legacy");
+
+    let sources = config.sources();
+    dbg!(&sources);
+    assert_eq!(
+        sources,
+        vec![
+            SourceInfo {
+                kind: SourceKind::OsEnv,
+                layer: Layer::Env,
+                conflict: Conflict::Error,
+            },
+            SourceInfo {
+                kind: SourceKind::Json(PathBuf::from("./tests/data/json/not_found.json")),
+                layer: Layer::Defaults,
+                conflict: Conflict::Error,
+            },
+            SourceInfo {
+                kind: SourceKind::Value(String::from("port")),
+                layer: Layer::Env,
+                conflict: Conflict::Error,
+            },
+            SourceInfo {
+                kind: SourceKind::Yaml(PathBuf::from("./tests/data/yaml/not_found.yaml")),
+                layer: Layer::File,
+                conflict: Conflict::LastWins,
+            },
+            SourceInfo {
+                kind: SourceKind::Args,
+                layer: Layer::Override,
+                conflict: Conflict::LastWins,
+            },
+            SourceInfo {
+                kind: SourceKind::Unset(String::from("legacy")),
+                layer: Layer::Override,
+                conflict: Conflict::LastWins,
+            },
+        ]
+    );
+
+    // files are only read on build
+    let cfg = config.build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+}
+
+// builder defined in static
+#[test]
+fn test_lazy_static_success() {
+    let cfg = CONFIG.build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.host, "app.example.com");
+    assert_eq!(cfg.port, 8080);
+}
+
+// changes of files and env vars are picked up by building again
+#[test]
+fn test_lazy_reload_success() {
+    let _lock = TEST_MUTEX.lock().unwrap();
+
+    let file_path = env::temp_dir().join("configrs_test_lazy_reload.json");
+    std::fs::write(&file_path, r#"{ "host": "localhost" }"#).unwrap();
+
+    #[derive(Debug, Deserialize)]
+    struct Reload {
+        host: String,
+        #[serde(alias = "LAZY_PORT")]
+        port: Option<u16>,
+    }
+
+    let config = Config::new().with_json(&file_path).with_env_prefix("LAZY_");
+
+    let cfg = config.build::<Reload>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.host, "localhost");
+    assert_eq!(cfg.port, None);
+
+    std::fs::write(&file_path, r#"{ "host": "app.example.com" }"#).unwrap();
+    unsafe {
+        env::set_var("LAZY_PORT", "8080");
+    }
+
+    let cfg = config.build::<Reload>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.host, "app.example.com");
+    assert_eq!(cfg.port, Some(8080));
+
+    unsafe {
+        env::remove_var("LAZY_PORT");
+    }
+    std::fs::remove_file(&file_path).unwrap();
+}
@@ -0,0 +1,158 @@
+// test normalized keys matching of sources
+
+use serde::Deserialize;
+
+use configrs::config::*;
+
+#[derive(Debug, Deserialize)]
+struct Server {
+    listen_port: u16,
+    host_name: String,
+    allowed_origins: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Cfg {
+    db_url: String,
+    db_max_connections: u32,
+    server: Server,
+}
+
+const ENV: &str = "./tests/data/env/test_normalize.env";
+const JSON: &str = "./tests/data/json/test_normalize.json";
+
+// upper case, kebab-case and camelCase keys fill snake_case fields
+#[test]
+fn test_normalize_success() {
+    let cfg = Config::new()
+        .with_env(ENV)
+        .with_json(JSON)
+        .with_args([
+            "--SERVER.listen-port=9090",
+            "--server.allowedOrigins=http://localhost",
+            "--server.allowedOrigins=http://127.0.0.1",
+        ])
+        .with_array_merge_at("server.allowedOrigins", ArrayMerge::Append)
+        .with_env_prefix("APP_")
+        .with_normalized_keys()
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.db_url, "postgres://env");
+    assert_eq!(cfg.db_max_connections, 20);
+    assert_eq!(cfg.server.listen_port, 9090);
+    assert_eq!(cfg.server.host_name, "localhost");
+    assert_eq!(
+        cfg.server.allowed_origins,
+        vec![
+            "https://example.com",
+            "http://localhost",
+            "http://127.0.0.1"
+        ]
+    );
+}
+
+// prefix of injected env vars is stripped before normalizing
+#[test]
+fn test_normalize_prefix_success() {
+    #[derive(Debug, Deserialize)]
+    struct Cfg {
+        database_url: String,
+        port: u16,
+    }
+
+    let cfg = Config::empty()
+        .with_env_vars([
+            ("APP_DATABASE_URL", "postgres://env"),
+            ("APP_PORT", "8080"),
+            ("DATABASE_URL", "postgres://unrelated"),
+        ])
+        .with_env_prefix("APP_")
+        .with_normalized_keys()
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.database_url, "postgres://env");
+    assert_eq!(cfg.port, 8080);
+}
+
+// keys are kept as they are without normalization
+#[test]
+fn test_normalize_disabled_failed() {
+    let cfg = Config::new()
+        .with_env(ENV)
+        .with_json(JSON)
+        .with_env_prefix("APP_")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+}
+
+// keys colliding after normalization, in the same source and in sources of the same layer
+#[test]
+fn test_normalize_collision_failed() {
+    let cfg = Config::new()
+        .with_json("./tests/data/json/test_normalize_collision.json")
+        .with_env_prefix("APP_")
+        .with_normalized_keys()
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Overwrite is false, found key: server.listen_port (server.listenPort, server.listen_port) is duplicated"
+    );
+
+    let cfg = Config::new()
+        .with_env(ENV)
+        .with_value("APP_db_url", "postgres://value")
+        .with_env_prefix("APP_")
+        .with_normalized_keys()
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Overwrite is false, found key: db_url (APP_DB_URL, APP_db_url) is duplicated"
+    );
+}
+
+// keys of map fields are normalized too, unless kept as they are with raw keys
+#[test]
+fn test_normalize_map_field_success() {
+    #[derive(Debug, Deserialize)]
+    struct Cfg {
+        service_name: String,
+        headers: std::collections::HashMap<String, String>,
+    }
+
+    const MAP: &str = "./tests/data/json/test_normalize_map.json";
+
+    let cfg = Config::empty()
+        .with_json(MAP)
+        .with_normalized_keys()
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Overwrite is false, found key: headers.x_request_id (headers.X-Request-Id, headers.x-request-id) is duplicated"
+    );
+
+    let cfg = Config::empty()
+        .with_json(MAP)
+        .with_args(["--HEADERS.X-Trace-Id=ghi"])
+        .with_normalized_keys()
+        .with_raw_keys_at("Headers")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.service_name, "app");
+    assert_eq!(cfg.headers.len(), 3);
+    assert_eq!(cfg.headers["X-Request-Id"], "abc");
+    assert_eq!(cfg.headers["x-request-id"], "def");
+    assert_eq!(cfg.headers["X-Trace-Id"], "ghi");
+}
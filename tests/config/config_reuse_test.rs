@@ -0,0 +1,128 @@
+// test building many types from the same config
+
+use serde::Deserialize;
+
+use configrs::config::*;
+
+const DEFAULTS: &str = "./tests/data/json/test_layer_defaults.json";
+const FILE: &str = "./tests/data/json/test_layer_file.json";
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Database {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+struct Server {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+struct DatabaseModule {
+    database: Database,
+}
+
+// build different types, including sub-structs, from the same sources
+#[test]
+fn test_reuse_build_many_success() {
+    let config = Config::new()
+        .with_json(DEFAULTS)
+        .with_layer(Layer::Defaults)
+        .with_json(FILE);
+
+    let server = config.build::<Server>();
+    dbg!(&server);
+    assert!(server.is_ok());
+    let server = server.unwrap();
+    assert_eq!(server.host, "app.example.com");
+    assert_eq!(server.port, 8080);
+
+    let module = config.build::<DatabaseModule>();
+    dbg!(&module);
+    assert!(module.is_ok());
+    assert_eq!(module.unwrap().database.port, 6543);
+
+    // same type again, and config still extendable
+    let again = config.build::<DatabaseModule>();
+    assert!(again.is_ok());
+
+    let config = config.with_args(["--database.port=7000"]);
+    let module = config.build::<DatabaseModule>();
+    dbg!(&module);
+    assert!(module.is_ok());
+    assert_eq!(module.unwrap().database.port, 7000);
+}
+
+// error is returned by every build
+#[test]
+fn test_reuse_build_many_failed() {
+    let config = Config::new().with_json("./tests/data/json/not_found.json");
+
+    let server = config.build::<Server>();
+    dbg!(&server);
+    assert!(server.is_err());
+
+    let module = config.build::<DatabaseModule>();
+    dbg!(&module);
+    assert!(module.is_err());
+    assert_eq!(server.unwrap_err(), module.unwrap_err());
+}
+
+// sources are read once by load, and its snapshot built into many types
+#[test]
+fn test_reuse_load_success() {
+    let file_path = std::env::temp_dir().join("configrs_test_reuse_load.json");
+    std::fs::write(
+        &file_path,
+        r#"{ "host": "localhost", "port": 8080, "database": { "host": "db", "port": 5432 } }"#,
+    )
+    .unwrap();
+
+    let config = Config::empty().with_json(&file_path);
+    let loaded = config.load();
+    assert!(loaded.is_ok());
+    let loaded = loaded.unwrap();
+
+    // changes after load are not in the snapshot
+    std::fs::write(
+        &file_path,
+        r#"{ "host": "app.example.com", "port": 9090, "database": { "host": "db", "port": 6543 } }"#,
+    )
+    .unwrap();
+
+    let server = loaded.build::<Server>();
+    dbg!(&server);
+    assert!(server.is_ok());
+    let server = server.unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 8080);
+
+    let database = loaded.build_at::<Database>("database");
+    dbg!(&database);
+    assert!(database.is_ok());
+    assert_eq!(database.unwrap().port, 5432);
+
+    // building the config reads the file again
+    let server = config.build::<Server>();
+    dbg!(&server);
+    assert!(server.is_ok());
+    assert_eq!(server.unwrap().port, 9090);
+
+    std::fs::remove_file(&file_path).unwrap();
+}
+
+// load fails like build, and build_at of the snapshot fails without value at key path
+#[test]
+fn test_reuse_load_failed() {
+    let config = Config::empty().with_json("./tests/data/json/not_found.json");
+    let loaded = config.load();
+    assert!(loaded.is_err());
+    assert_eq!(loaded.err().unwrap(), config.build::<Server>().unwrap_err());
+
+    let loaded = Config::empty().with_json(FILE).load().unwrap();
+    let database = loaded.build_at::<Database>("missing");
+    dbg!(&database);
+    assert!(database.is_err());
+}
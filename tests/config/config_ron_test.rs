@@ -0,0 +1,129 @@
+// test file for loading configuration from ron file
+
+use serde::{Deserialize, Serialize};
+
+use configrs::config::*;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub enum Mode {
+    Slow,
+    Fast,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub enum Retry {
+    Never,
+    Limited(u32),
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub enum Color {
+    Rgb(u8, u8, u8),
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub enum Listen {
+    Socket(String),
+    Address { host: String, port: u16 },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubEnv {
+    #[serde(alias = "subEnvString")]
+    pub sub_env_string: String,
+    #[serde(alias = "subEnvInteger")]
+    pub sub_env_integer: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Env {
+    #[serde(alias = "envString")]
+    pub string: String,
+    #[serde(alias = "envInteger")]
+    pub integer: i64,
+    #[serde(alias = "envFloat")]
+    pub float: f64,
+    #[serde(alias = "envBoolean")]
+    pub boolean: bool,
+    #[serde(alias = "envArr")]
+    pub arr: Vec<String>,
+    #[serde(alias = "envOptional")]
+    pub optional: Option<String>,
+    #[serde(alias = "envNone")]
+    pub none: Option<String>,
+    #[serde(alias = "envTuple")]
+    pub tuple: (i32, String),
+    #[serde(alias = "subEnv")]
+    pub sub_env: SubEnv,
+    pub mode: Mode,
+    pub retry: Retry,
+    pub color: Color,
+    pub listen: Listen,
+    pub modes: Vec<Mode>,
+}
+
+// load ron and success, including enums and tuples
+#[test]
+fn test_ron_success() {
+    let file_path = "./tests/data/ron/test.ron";
+
+    let cfg = Config::new().with_ron(file_path).build::<Env>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.string, "string");
+    assert_eq!(cfg.integer, 123);
+    assert_eq!(cfg.float, 123.0);
+    assert!(cfg.boolean);
+    assert_eq!(cfg.arr, vec!["anu", "nganu", "lskmdf", "lwkef", "lkemrg"]);
+    assert_eq!(cfg.optional, Some("some".to_string()));
+    assert_eq!(cfg.none, None);
+    assert_eq!(cfg.tuple, (1, "one".to_string()));
+    assert_eq!(cfg.sub_env.sub_env_string, "string");
+    assert_eq!(cfg.sub_env.sub_env_integer, 123);
+    assert_eq!(cfg.mode, Mode::Fast);
+    assert_eq!(cfg.retry, Retry::Limited(3));
+    assert_eq!(cfg.color, Color::Rgb(255, 128, 0));
+    assert_eq!(
+        cfg.listen,
+        Listen::Address {
+            host: "localhost".to_string(),
+            port: 8080
+        }
+    );
+    assert_eq!(cfg.modes, vec![Mode::Slow, Mode::Fast]);
+}
+
+// enum variants are kept as single-key tables in Value
+#[test]
+fn test_ron_enum_as_value_success() {
+    #[derive(Debug, Deserialize)]
+    struct Cfg {
+        retry: Value,
+        mode: Value,
+    }
+
+    let cfg = Config::new()
+        .with_ron("./tests/data/ron/test.ron")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(
+        cfg.retry,
+        Value::Map([("Limited".to_string(), Value::Int64(3))].into())
+    );
+    assert_eq!(cfg.mode, Value::String("Fast".to_string()));
+}
+
+// invalid ron is reported with line and column
+#[test]
+fn test_ron_invalid_failed() {
+    let cfg = Config::new()
+        .with_ron("./tests/data/ron/test_invalid.ron")
+        .build::<Env>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    let err = cfg.unwrap_err().to_string();
+    assert!(err.starts_with("[CONFIG][ERROR] Ron parsing error: 3:21:"));
+}
@@ -0,0 +1,145 @@
+// test JSON Schema generation and validation of merged configs
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use configrs::config::*;
+
+const FILE: &str = "./tests/data/yaml/test_schema.yaml";
+const SCHEMA: &str = "./tests/data/json/test_schema.json";
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct Database {
+    host: String,
+    port: u16,
+    password: Option<Secret<String>>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct Cfg {
+    name: String,
+    database: Database,
+}
+
+// schema of type, exported and used to validate
+#[test]
+fn test_schema_generated_success() {
+    let schema = json_schema::<Cfg>();
+    dbg!(&schema);
+    assert_eq!(schema["required"], serde_json::json!(["name", "database"]));
+    assert_eq!(
+        schema["$defs"]["Database"]["properties"]["password"]["type"],
+        serde_json::json!(["string", "null"])
+    );
+
+    let cfg = Config::empty()
+        .with_yaml(FILE)
+        .with_args(["--database.password=hunter2"])
+        .build_with_schema::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.name, "app");
+    assert_eq!(cfg.database.host, "db.example.com");
+    assert_eq!(cfg.database.port, 5432);
+    assert_eq!(cfg.database.password.unwrap().expose(), "hunter2");
+}
+
+// every violation with its JSON pointer and source
+#[test]
+fn test_schema_generated_failed() {
+    let cfg = Config::empty()
+        .with_yaml(FILE)
+        .with_args(["--database.port=70000"])
+        .with_value("name", 5)
+        .build_with_schema::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    let err = cfg.unwrap_err();
+    let violations = err
+        .schema_violations()
+        .iter()
+        .map(|v| (v.pointer.as_str(), v.source.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        violations,
+        vec![
+            ("/database/port", Some(SourceKind::Args)),
+            ("/name", Some(SourceKind::Value("name".to_string()))),
+        ]
+    );
+    assert!(err.to_string().contains(
+        "/database/port: value is greater than the maximum of 65535 (from command-line arguments)"
+    ));
+}
+
+// values are not shown in violations, they may be secrets
+#[test]
+fn test_schema_masked() {
+    let cfg = Config::empty()
+        .with_yaml(FILE)
+        .with_args(["--database.password=hunter2"])
+        .with_json_schema(serde_json::json!({
+            "properties": {
+                "database": {
+                    "properties": { "password": { "type": "string", "minLength": 12 } }
+                }
+            }
+        }))
+        .build_with_schema::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    let err = cfg.unwrap_err();
+    assert_eq!(
+        err.schema_violations()[0].message,
+        "value is shorter than 12 characters"
+    );
+    assert!(!err.to_string().contains("hunter2"));
+}
+
+// schema file, checked by build, with missing keys
+#[test]
+fn test_schema_file() {
+    #[derive(Debug, Deserialize)]
+    struct Cfg {
+        name: String,
+    }
+
+    let config = Config::empty()
+        .with_yaml(FILE)
+        .with_json_schema_file(SCHEMA);
+    let cfg = config.build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_eq!(cfg.unwrap().name, "app");
+
+    let cfg = config
+        .clone()
+        .with_args(["--database.port=80"])
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    let err = cfg.unwrap_err();
+    assert_eq!(err.schema_violations().len(), 1);
+    assert_eq!(err.schema_violations()[0].pointer, "/database/port");
+    assert_eq!(err.schema_violations()[0].source, Some(SourceKind::Args));
+
+    // missing key, reported at the table supplied by the file
+    let cfg = config.without_key("database.host").build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    let err = cfg.unwrap_err();
+    assert_eq!(err.schema_violations()[0].pointer, "/database");
+    assert_eq!(
+        err.schema_violations()[0].source,
+        Some(SourceKind::Yaml(FILE.into()))
+    );
+
+    let cfg = Config::empty()
+        .with_yaml(FILE)
+        .with_json_schema_file("./tests/data/json/not_found.json")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert!(cfg.unwrap_err().schema_violations().is_empty());
+}
@@ -0,0 +1,107 @@
+// test SOPS encrypted files decrypted with a local age identity
+
+use serde::Deserialize;
+
+use configrs::config::*;
+
+const YAML: &str = "./tests/data/yaml/test_sops.yaml";
+const JSON: &str = "./tests/data/json/test_sops.json";
+const IDENTITY: &str = "./tests/data/key/test_age.txt";
+
+#[derive(Debug, Deserialize)]
+struct Database {
+    host: String,
+    port: u16,
+    password: Secret<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Cfg {
+    database: Database,
+    debug: bool,
+    hosts: Vec<String>,
+    port_unencrypted: u16,
+}
+
+fn assert_cfg(cfg: Cfg) {
+    assert_eq!(cfg.database.host, "db.example.com");
+    assert_eq!(cfg.database.port, 5432);
+    assert_eq!(cfg.database.password.expose(), "hunter2");
+    assert!(cfg.debug);
+    assert_eq!(cfg.hosts, vec!["a.example.com", "b.example.com"]);
+    assert_eq!(cfg.port_unencrypted, 8080);
+}
+
+// decrypt yaml with identity file, merged with other sources
+#[test]
+fn test_sops_yaml_success() {
+    let cfg = Config::empty()
+        .with_sops_yaml(YAML)
+        .with_age_identity_file(IDENTITY)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_cfg(cfg.unwrap());
+
+    let cfg = Config::empty()
+        .with_sops_yaml(YAML)
+        .with_args(["--database.port=6543"])
+        .with_age_identity_file(IDENTITY)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_eq!(cfg.unwrap().database.port, 6543);
+}
+
+// decrypt json with identity file from injected SOPS_AGE_KEY_FILE env var
+#[test]
+fn test_sops_json_env_success() {
+    let cfg = Config::empty()
+        .with_env_vars([("SOPS_AGE_KEY_FILE", IDENTITY)])
+        .with_sops_json(JSON)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_cfg(cfg.unwrap());
+}
+
+// wrong identity, tampered file, missing identity file
+#[test]
+fn test_sops_failed() {
+    let cfg = Config::empty()
+        .with_sops_yaml(YAML)
+        .with_age_identity_file("./tests/data/key/test_age_other.txt")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Sops error: no age identity can decrypt the data key"
+    );
+
+    let cfg = Config::empty()
+        .with_sops_yaml("./tests/data/yaml/test_sops_tampered.yaml")
+        .with_age_identity_file(IDENTITY)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Sops error: MAC mismatch, the file has been modified"
+    );
+
+    let cfg = Config::empty()
+        .with_sops_yaml(YAML)
+        .with_age_identity_file("./tests/data/key/test_age_not_found.txt")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+
+    // plain yaml without sops metadata
+    let cfg = Config::empty()
+        .with_sops_yaml("./tests/data/yaml/test.yaml")
+        .with_age_identity_file(IDENTITY)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+}
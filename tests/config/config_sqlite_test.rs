@@ -0,0 +1,155 @@
+// test configurations from SQLite database
+
+use serde::Deserialize;
+
+use configrs::config::*;
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+
+// new database with settings table, typed by type column for some rows
+fn database(name: &str) -> PathBuf {
+    let file_path = env::temp_dir().join(name);
+    let _ = std::fs::remove_file(&file_path);
+    let conn = rusqlite::Connection::open(&file_path).unwrap();
+    conn.execute_batch(
+        r#"
+        CREATE TABLE settings (key TEXT, value, type TEXT, device TEXT);
+        INSERT INTO settings VALUES
+            ('database.host', 'db.example.com', NULL, NULL),
+            ('database.port', 5432, NULL, NULL),
+            ('database.password', '12345', 'string', NULL),
+            ('debug', 'true', NULL, NULL),
+            ('ratio', '0.5', 'float', NULL),
+            ('labels', '{"team": "core"}', 'json', NULL);
+        CREATE TABLE devices (name TEXT, val, device TEXT);
+        INSERT INTO devices VALUES
+            ('enabled', 1, 'edge-1'),
+            ('enabled', 0, 'edge-2');
+        "#,
+    )
+    .unwrap();
+    file_path
+}
+
+#[derive(Debug, Deserialize)]
+struct Database {
+    host: String,
+    port: u16,
+    password: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Cfg {
+    database: Database,
+    debug: bool,
+    ratio: f64,
+    labels: HashMap<String, String>,
+}
+
+// rows of table, dotted keys into nested values
+#[test]
+fn test_sqlite_success() {
+    let file_path = database("configrs_test_sqlite.db");
+
+    let cfg = Config::empty()
+        .with_sqlite(&file_path, "settings")
+        .with_args(["--database.port=6432"])
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.database.host, "db.example.com");
+    assert_eq!(cfg.database.port, 6432);
+    assert_eq!(cfg.database.password, "12345");
+    assert!(cfg.debug);
+    assert_eq!(cfg.ratio, 0.5);
+    assert_eq!(cfg.labels["team"], "core");
+
+    let _ = std::fs::remove_file(&file_path);
+}
+
+// rows of query, with columns aliased to key and value
+#[test]
+fn test_sqlite_query_success() {
+    let file_path = database("configrs_test_sqlite_query.db");
+
+    #[derive(Debug, Deserialize)]
+    struct Cfg {
+        enabled: i64,
+    }
+
+    let cfg = Config::empty()
+        .with_sqlite_query(
+            &file_path,
+            "SELECT name AS key, val AS value FROM devices WHERE device = 'edge-2'",
+        )
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_eq!(cfg.unwrap().enabled, 0);
+
+    let _ = std::fs::remove_file(&file_path);
+}
+
+// missing database, table or columns, invalid typed value
+#[test]
+fn test_sqlite_failed() {
+    let file_path = database("configrs_test_sqlite_failed.db");
+
+    let cfg = Config::empty()
+        .with_sqlite("./tests/data/not_found.db", "settings")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+
+    let cfg = Config::empty()
+        .with_sqlite(&file_path, "not_found")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+
+    let cfg = Config::empty()
+        .with_sqlite(&file_path, "devices")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+
+    let conn = rusqlite::Connection::open(&file_path).unwrap();
+    conn.execute("UPDATE settings SET value = 'abc' WHERE key = 'ratio'", [])
+        .unwrap();
+    let cfg = Config::empty()
+        .with_sqlite(&file_path, "settings")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Sqlite error: invalid value of key ratio"
+    );
+
+    let _ = std::fs::remove_file(&file_path);
+}
+
+// watcher detects changes committed by other connections
+#[test]
+fn test_sqlite_watcher_success() {
+    let file_path = database("configrs_test_sqlite_watcher.db");
+    let config = Config::empty().with_sqlite(&file_path, "settings");
+
+    let mut watcher = SqliteWatcher::new(&file_path).unwrap();
+    assert!(!watcher.changed().unwrap());
+    assert!(config.build::<Cfg>().unwrap().debug);
+
+    let conn = rusqlite::Connection::open(&file_path).unwrap();
+    conn.execute(
+        "UPDATE settings SET value = 'false' WHERE key = 'debug'",
+        [],
+    )
+    .unwrap();
+    assert!(watcher.changed().unwrap());
+    assert!(!watcher.changed().unwrap());
+    assert!(!config.build::<Cfg>().unwrap().debug);
+
+    let _ = std::fs::remove_file(&file_path);
+}
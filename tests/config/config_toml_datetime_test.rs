@@ -0,0 +1,86 @@
+// test toml datetimes are preserved through the pipeline
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use serde::Deserialize;
+
+use configrs::config::*;
+
+// toml datetimes into chrono types
+#[test]
+fn test_toml_datetime_chrono_success() {
+    #[derive(Debug, Deserialize)]
+    struct Sub {
+        dates: Vec<NaiveDate>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Cfg {
+        #[serde(rename = "offsetDatetime")]
+        offset_datetime: DateTime<FixedOffset>,
+        #[serde(rename = "offsetDatetimeUtc")]
+        offset_datetime_utc: DateTime<Utc>,
+        #[serde(rename = "localDatetime")]
+        local_datetime: NaiveDateTime,
+        #[serde(rename = "localDate")]
+        local_date: NaiveDate,
+        #[serde(rename = "localTime")]
+        local_time: NaiveTime,
+        sub: Sub,
+    }
+
+    let cfg = Config::new()
+        .with_toml("./tests/data/toml/test_datetime.toml")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(
+        cfg.offset_datetime,
+        DateTime::parse_from_rfc3339("1979-05-27T07:32:00-08:00").unwrap()
+    );
+    assert_eq!(
+        cfg.offset_datetime_utc,
+        DateTime::parse_from_rfc3339("1979-05-27T07:32:00Z").unwrap()
+    );
+    assert_eq!(
+        cfg.local_datetime,
+        NaiveDate::from_ymd_opt(1979, 5, 27)
+            .unwrap()
+            .and_hms_micro_opt(7, 32, 0, 999999)
+            .unwrap()
+    );
+    assert_eq!(cfg.local_date, NaiveDate::from_ymd_opt(1979, 5, 27).unwrap());
+    assert_eq!(cfg.local_time, NaiveTime::from_hms_opt(7, 32, 0).unwrap());
+    assert_eq!(
+        cfg.sub.dates,
+        vec![
+            NaiveDate::from_ymd_opt(1979, 5, 27).unwrap(),
+            NaiveDate::from_ymd_opt(1980, 1, 1).unwrap()
+        ]
+    );
+}
+
+// toml datetimes into strings and Value
+#[test]
+fn test_toml_datetime_string_success() {
+    #[derive(Debug, Deserialize)]
+    struct Cfg {
+        #[serde(rename = "offsetDatetime")]
+        offset_datetime: String,
+        #[serde(rename = "localDate")]
+        local_date: String,
+        #[serde(rename = "localTime")]
+        local_time: Value,
+    }
+
+    let cfg = Config::new()
+        .with_toml("./tests/data/toml/test_datetime.toml")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.offset_datetime, "1979-05-27T07:32:00-08:00");
+    assert_eq!(cfg.local_date, "1979-05-27");
+    assert_eq!(cfg.local_time, Value::String("07:32:00".to_string()));
+    assert_eq!(cfg.local_time.to_string(), "07:32:00");
+}
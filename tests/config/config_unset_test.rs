@@ -0,0 +1,130 @@
+// test removing keys set by lower layers
+
+use serde::Deserialize;
+
+use configrs::config::*;
+
+#[derive(Debug, Deserialize)]
+struct Section {
+    enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Legacy {
+    section: Option<Section>,
+    port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+struct Cfg {
+    host: String,
+    timeout: Option<u32>,
+    legacy: Option<Legacy>,
+}
+
+const BASE: &str = "./tests/data/yaml/test_unset_base.yaml";
+const OVERLAY: &str = "./tests/data/yaml/test_unset_overlay.yaml";
+
+// remove nested and top level keys of lower layers
+#[test]
+fn test_without_key_success() {
+    let cfg = Config::new()
+        .with_yaml(BASE)
+        .without_key("legacy.section")
+        .without_key("timeout")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.host, "localhost");
+    assert_eq!(cfg.timeout, None);
+    let legacy = cfg.legacy.unwrap();
+    assert!(legacy.section.is_none());
+    assert_eq!(legacy.port, 8000);
+
+    let cfg = Config::new()
+        .with_yaml(BASE)
+        .without_key("legacy")
+        .without_key("missing.key")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert!(cfg.unwrap().legacy.is_none());
+}
+
+// removed key in lower layer is set again by higher layer
+#[test]
+fn test_without_key_layer_success() {
+    let cfg = Config::new()
+        .with_value("timeout", 60)
+        .with_yaml(BASE)
+        .without_key("timeout")
+        .with_layer(Layer::File)
+        .with_conflict(Conflict::LastWins)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.timeout, Some(60));
+    assert!(cfg.legacy.unwrap().section.unwrap().enabled);
+}
+
+// removed key set by source of the same layer
+#[test]
+fn test_without_key_failed() {
+    let cfg = Config::new()
+        .with_args(["--timeout=10"])
+        .without_key("timeout")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Overwrite is false, found key: timeout is duplicated"
+    );
+}
+
+// keys tagged `!unset` in yaml remove keys of lower layers
+#[test]
+fn test_yaml_unset_success() {
+    let cfg = Config::new()
+        .with_yaml(BASE)
+        .with_layer(Layer::Defaults)
+        .with_yaml(OVERLAY)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.host, "app.example.com");
+    assert_eq!(cfg.timeout, None);
+    let legacy = cfg.legacy.unwrap();
+    assert!(legacy.section.is_none());
+    assert_eq!(legacy.port, 9000);
+}
+
+// keys tagged `!unset` in yaml of the same layer require `Conflict::LastWins`
+#[test]
+fn test_yaml_unset_same_layer_success() {
+    let cfg = Config::new()
+        .with_yaml(BASE)
+        .with_yaml(OVERLAY)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Overwrite is false, found key: host is duplicated"
+    );
+
+    let cfg = Config::new()
+        .with_yaml(BASE)
+        .with_yaml(OVERLAY)
+        .with_conflict(Conflict::LastWins)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.host, "app.example.com");
+    assert_eq!(cfg.timeout, None);
+    assert!(cfg.legacy.unwrap().section.is_none());
+}
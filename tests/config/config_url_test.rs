@@ -0,0 +1,199 @@
+// test configurations fetched from HTTP url
+
+use serde::Deserialize;
+
+use configrs::config::*;
+use std::env;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use super::http_stub::{Stub, header, response, unreachable_url};
+
+const JSON: &str = r#"{ "host": "remote.example.com", "port": 8080 }"#;
+
+#[derive(Debug, Deserialize)]
+struct Cfg {
+    host: String,
+    port: u16,
+}
+
+// fetch json with auth header, merged like a file source
+#[test]
+fn test_url_json_success() {
+    let stub = Stub::serve(|request| match header(request, "authorization") {
+        Some("Bearer secret") => response("200 OK", &[], JSON),
+        _ => response("401 Unauthorized", &[], ""),
+    });
+
+    let cfg = Config::empty()
+        .with_url(&format!("{}/app.json", stub.url), Format::Json)
+        .with_header("Authorization", "Bearer secret")
+        .with_timeout(Duration::from_secs(5))
+        .with_args(["--port=9090"])
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.host, "remote.example.com");
+    assert_eq!(cfg.port, 9090);
+    assert!(stub.requests()[0].starts_with("GET /app.json"));
+}
+
+// fetch toml and yaml
+#[test]
+fn test_url_formats_success() {
+    let stub = Stub::serve(|request| {
+        if request.starts_with("GET /app.toml") {
+            response("200 OK", &[], "host = \"toml.example.com\"\nport = 1")
+        } else {
+            response("200 OK", &[], "host: yaml.example.com\nport: 2")
+        }
+    });
+
+    let cfg = Config::empty()
+        .with_url(&format!("{}/app.toml", stub.url), Format::Toml)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert_eq!(cfg.unwrap().host, "toml.example.com");
+
+    let cfg = Config::empty()
+        .with_url(&format!("{}/app.yaml", stub.url), Format::Yaml)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert_eq!(cfg.unwrap().port, 2);
+}
+
+// rejected request and invalid document are errors
+#[test]
+fn test_url_failed() {
+    let stub = Stub::serve(|request| match header(request, "authorization") {
+        Some(_) => response("200 OK", &[], "{ invalid"),
+        None => response("401 Unauthorized", &[], ""),
+    });
+    let url = format!("{}/app.json", stub.url);
+
+    let cfg = Config::empty().with_url(&url, Format::Json).build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        format!("[CONFIG][ERROR] Http error: {}: status 401", url)
+    );
+
+    let cfg = Config::empty()
+        .with_url(&url, Format::Json)
+        .with_header("Authorization", "Bearer secret")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+}
+
+// request options declared after a source that is not remote, or before any source
+#[test]
+fn test_url_option_failed() {
+    let cfg = Config::empty()
+        .with_json("./tests/data/json/test.json")
+        .with_header("Authorization", "Bearer secret")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Remote source option error: with_header requires a url, Consul, etcd or Vault source declared right before it, found: json file ./tests/data/json/test.json"
+    );
+
+    let cfg = Config::empty()
+        .with_env_vars([("port", "8080")])
+        .with_cache(env::temp_dir().join("configrs_test_url_option.json"))
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+
+    let cfg = Config::empty()
+        .with_timeout(Duration::from_secs(1))
+        .with_url("http://127.0.0.1:1/app.json", Format::Json)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert!(cfg.unwrap_err().to_string().ends_with("found: no source"));
+}
+
+// cached copy is used when server answers not modified
+#[test]
+fn test_url_etag_cache_success() {
+    let cache = env::temp_dir().join("configrs_test_url_etag.json");
+    let _ = std::fs::remove_file(&cache);
+
+    let stub = Stub::serve(|request| match header(request, "if-none-match") {
+        Some("\"v1\"") => response("304 Not Modified", &[], ""),
+        _ => response("200 OK", &[("ETag", "\"v1\"")], JSON),
+    });
+    let config = Config::empty()
+        .with_url(&stub.url, Format::Json)
+        .with_cache(&cache);
+
+    let cfg = config.build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_eq!(std::fs::read_to_string(&cache).unwrap(), JSON);
+
+    let cfg = config.build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_eq!(cfg.unwrap().host, "remote.example.com");
+
+    let requests = stub.requests();
+    assert_eq!(header(&requests[0], "if-none-match"), None);
+    assert_eq!(header(&requests[1], "if-none-match"), Some("\"v1\""));
+
+    let _ = std::fs::remove_file(&cache);
+}
+
+// server errors are retried
+#[test]
+fn test_url_retries_success() {
+    let count = Arc::new(AtomicU32::new(0));
+    let counter = count.clone();
+    let stub = Stub::serve(move |_| match counter.fetch_add(1, Ordering::SeqCst) {
+        0 | 1 => response("503 Service Unavailable", &[], ""),
+        _ => response("200 OK", &[], JSON),
+    });
+
+    let cfg = Config::empty()
+        .with_url(&stub.url, Format::Json)
+        .with_retries(1)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+
+    count.store(0, Ordering::SeqCst);
+    let cfg = Config::empty()
+        .with_url(&stub.url, Format::Json)
+        .with_retries(2)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_eq!(stub.requests().len(), 5);
+}
+
+// cached copy is used when server is unreachable
+#[test]
+fn test_url_unreachable_cache_success() {
+    let cache = env::temp_dir().join("configrs_test_url_unreachable.json");
+    std::fs::write(&cache, JSON).unwrap();
+    let url = unreachable_url();
+
+    let cfg = Config::empty()
+        .with_url(&url, Format::Json)
+        .with_cache(&cache)
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert_eq!(cfg.unwrap().port, 8080);
+
+    let _ = std::fs::remove_file(&cache);
+    let cfg = Config::empty().with_url(&url, Format::Json).build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+}
@@ -0,0 +1,183 @@
+// test validation rules checked after build
+
+use serde::Deserialize;
+
+use configrs::config::*;
+
+const FILE: &str = "./tests/data/toml/test_validate.toml";
+
+#[derive(Debug, Deserialize)]
+struct Database {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+struct Worker {
+    name: String,
+    threads: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct Cfg {
+    name: String,
+    database: Database,
+    workers: Vec<Worker>,
+    url: Option<String>,
+    socket: Option<String>,
+}
+
+impl Validate for Database {
+    fn validate(&self, v: &mut Validator) {
+        v.check("host", !self.host.is_empty(), "must not be empty")
+            .check("port", self.port >= 1024, "must be at least 1024");
+    }
+}
+
+impl Validate for Worker {
+    fn validate(&self, v: &mut Validator) {
+        v.check(
+            "threads",
+            (1..=16).contains(&self.threads),
+            "must be in 1..=16",
+        );
+    }
+}
+
+impl Validate for Cfg {
+    fn validate(&self, v: &mut Validator) {
+        v.check("name", !self.name.is_empty(), "must not be empty")
+            .nested("database", &self.database)
+            .nested("workers", &self.workers);
+        if self.url.is_none() && self.socket.is_none() {
+            v.error("", "either url or socket must be set");
+        }
+    }
+}
+
+// rules passed
+#[test]
+fn test_validate_success() {
+    let cfg = Config::empty()
+        .with_toml(FILE)
+        .with_args(["--url=https://app.example.com"])
+        .build_validated::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.database.port, 5432);
+    assert_eq!(cfg.workers[1].name, "billing");
+}
+
+// every failure with its key path, in one error
+#[test]
+fn test_validate_failed() {
+    let cfg = Config::empty()
+        .with_toml("./tests/data/toml/test_validate_invalid.toml")
+        .build_validated::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    let err = cfg.unwrap_err();
+    let failures = err
+        .validation_errors()
+        .iter()
+        .map(|e| (e.key_path.as_str(), e.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        failures,
+        vec![
+            ("name", "must not be empty"),
+            ("database.port", "must be at least 1024"),
+            ("workers.1.threads", "must be in 1..=16"),
+            ("", "either url or socket must be set"),
+        ]
+    );
+    assert_eq!(err.key_path(), Some("name"));
+    assert!(
+        err.to_string()
+            .contains("database.port: must be at least 1024")
+    );
+
+    // failure of the config itself, without key path
+    let cfg = Config::empty().with_toml(FILE).build_validated::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    let err = cfg.unwrap_err();
+    assert_eq!(err.key_path(), None);
+    assert!(
+        err.to_string()
+            .ends_with("Validation failed: either url or socket must be set")
+    );
+
+    // deserialization errors are not validation errors
+    let cfg = Config::empty()
+        .with_toml(FILE)
+        .with_args(["--database.port=-1"])
+        .build_validated::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert!(cfg.unwrap_err().validation_errors().is_empty());
+}
+
+// rules generated by validator derive, with custom rules
+#[cfg(feature = "validator")]
+#[test]
+fn test_validate_derived() {
+    // in scope for nested rules generated by the derive
+    use validator::Validate as _;
+
+    #[derive(Debug, Deserialize, validator::Validate)]
+    struct Database {
+        #[validate(length(min = 1))]
+        host: String,
+        #[validate(range(min = 1024, message = "must be at least 1024"))]
+        port: u16,
+    }
+
+    #[derive(Debug, Deserialize, validator::Validate)]
+    struct Cfg {
+        #[validate(length(min = 3))]
+        name: String,
+        #[validate(nested)]
+        database: Database,
+    }
+
+    impl Validate for Cfg {
+        fn validate(&self, v: &mut Validator) {
+            v.derived(self).check(
+                "database.host",
+                self.database.host != "localhost",
+                "must not be localhost",
+            );
+        }
+    }
+
+    let cfg = Config::empty()
+        .with_toml(FILE)
+        .with_args([
+            "--name=a",
+            "--database.port=80",
+            "--database.host=localhost",
+        ])
+        .build_validated::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    let err = cfg.unwrap_err();
+    let failures = err
+        .validation_errors()
+        .iter()
+        .map(|e| (e.key_path.as_str(), e.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        failures,
+        vec![
+            ("database.port", "must be at least 1024"),
+            ("name", "length"),
+            ("database.host", "must not be localhost"),
+        ]
+    );
+
+    let cfg = Config::empty().with_toml(FILE).build_validated::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+}
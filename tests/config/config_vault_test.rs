@@ -0,0 +1,158 @@
+// test configurations from Vault KV v2 secrets
+
+use serde::Deserialize;
+
+use configrs::config::*;
+use std::time::Duration;
+
+use super::http_stub::{Stub, header, response};
+
+const SECRET: &str = r#"{
+    "request_id": "5e246671-ec05-6fc8-9f93-4fe4512f34ab",
+    "lease_id": "",
+    "renewable": false,
+    "lease_duration": 0,
+    "data": {
+        "data": { "username": "app", "password": "hunter2", "pool": { "size": 5 }, "tls.cert": "PEM" },
+        "metadata": { "created_time": "2026-01-02T03:04:05.000000Z", "version": 3 }
+    }
+}"#;
+
+// stub of secret `app/db` of engine mounted at `secret`, requiring token
+fn serve() -> Stub {
+    Stub::serve(|request| {
+        if header(request, "x-vault-token") != Some("s.token") {
+            return response("403 Forbidden", &[], r#"{"errors":["permission denied"]}"#);
+        }
+        if request.starts_with("GET /v1/secret/data/app/db ") {
+            response("200 OK", &[], SECRET)
+        } else {
+            response("404 Not Found", &[], r#"{"errors":[]}"#)
+        }
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct Pool {
+    size: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct Credentials {
+    username: String,
+    password: Secret<String>,
+    pool: Pool,
+    #[serde(rename = "tls.cert")]
+    tls_cert: String,
+}
+
+// merge secret fields at top level, redacted when debug printed
+#[test]
+fn test_vault_success() {
+    let stub = serve();
+
+    let cfg = Config::empty()
+        .with_vault(&stub.url, "/secret/", "app/db")
+        .with_header("X-Vault-Token", "s.token")
+        .with_value("username", "override")
+        .with_layer(Layer::Override)
+        .build::<Credentials>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.username, "override");
+    assert_eq!(cfg.password.expose(), "hunter2");
+    assert_eq!(cfg.pool.size, 5);
+    assert_eq!(cfg.tls_cert, "PEM");
+    assert!(!format!("{:?}", cfg).contains("hunter2"));
+}
+
+// merge secret fields under subtree, next to the fields of other sources
+#[test]
+fn test_vault_subtree_success() {
+    let stub = serve();
+
+    #[derive(Debug, Deserialize)]
+    struct Database {
+        host: String,
+        credentials: Credentials,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Cfg {
+        database: Database,
+    }
+
+    let cfg = Config::empty()
+        .with_args(["--database.host=db.example.com"])
+        .with_vault_at(&stub.url, "secret", "app/db", "database.credentials")
+        .with_header("X-Vault-Token", "s.token")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.database.host, "db.example.com");
+    assert_eq!(cfg.database.credentials.username, "app");
+    assert_eq!(cfg.database.credentials.password.expose(), "hunter2");
+}
+
+// missing token and missing secret
+#[test]
+fn test_vault_failed() {
+    let stub = serve();
+
+    let cfg = Config::empty()
+        .with_vault(&stub.url, "secret", "app/db")
+        .build::<Credentials>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        format!(
+            "[CONFIG][ERROR] Http error: {}/v1/secret/data/app/db: status 403",
+            stub.url
+        )
+    );
+
+    let cfg = Config::empty()
+        .with_vault(&stub.url, "secret", "app/missing")
+        .with_header("X-Vault-Token", "s.token")
+        .build::<Credentials>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+
+    let cfg = Config::empty()
+        .with_vault(&stub.url, "secret", "app/db")
+        .with_header("X-Vault-Token", "s.token")
+        .with_cache(std::env::temp_dir().join("configrs_test_vault_cache.json"))
+        .build::<Credentials>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Remote source option error: with_cache is not supported for Vault sources, secrets would be stored in plain text"
+    );
+}
+
+// lease and version of secrets
+#[test]
+fn test_vault_metadata_success() {
+    let stub = serve();
+
+    let metadata = Config::new()
+        .with_vault(&stub.url, "secret", "app/db")
+        .with_header("X-Vault-Token", "s.token")
+        .vault_metadata();
+    dbg!(&metadata);
+    assert!(metadata.is_ok());
+    let metadata = metadata.unwrap();
+    assert_eq!(metadata.len(), 1);
+    assert_eq!(
+        metadata[0].url,
+        format!("{}/v1/secret/data/app/db", stub.url)
+    );
+    assert_eq!(metadata[0].version, 3);
+    assert_eq!(metadata[0].created_time, "2026-01-02T03:04:05.000000Z");
+    assert_eq!(metadata[0].lease_duration, Duration::ZERO);
+    assert!(!metadata[0].renewable);
+}
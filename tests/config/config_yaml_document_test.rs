@@ -0,0 +1,140 @@
+// test loading multi-document yaml files and yaml merge keys
+
+use serde::{Deserialize, Serialize};
+
+use configrs::config::*;
+
+// every document is layered in order
+#[test]
+fn test_yaml_multi_document_success() {
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Cfg {
+        #[serde(rename = "envString")]
+        string: String,
+        #[serde(rename = "envInteger")]
+        integer: i64,
+        #[serde(rename = "envFloat")]
+        float: f64,
+        #[serde(rename = "envBoolean")]
+        boolean: bool,
+        #[serde(rename = "envArr")]
+        arr: Vec<String>,
+    }
+
+    let cfg = Config::new()
+        .with_yaml("./tests/data/yaml/test_multi_document.yaml")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.string, "string");
+    assert_eq!(cfg.integer, 123);
+    assert_eq!(cfg.float, 123.0);
+    assert!(cfg.boolean);
+    assert_eq!(cfg.arr, vec!["anu", "nganu"]);
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Overwrite {
+    #[serde(rename = "envString")]
+    string: String,
+    #[serde(rename = "envInteger")]
+    integer: i64,
+}
+
+// later document overwrites earlier one of the same file, without overwrite
+#[test]
+fn test_yaml_multi_document_later_wins_success() {
+    let cfg = Config::new()
+        .with_yaml("./tests/data/yaml/test_multi_document_overwrite.yaml")
+        .build::<Overwrite>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.string, "overlay");
+    assert_eq!(cfg.integer, 123);
+}
+
+// same key in documents of another file without overwrite
+#[test]
+fn test_yaml_multi_document_duplicate_failed() {
+    let cfg = Config::new()
+        .with_yaml("./tests/data/yaml/test_multi_document_overwrite.yaml")
+        .with_yaml("./tests/data/yaml/test_multi_document_overwrite.yaml")
+        .build::<Overwrite>();
+    dbg!(&cfg);
+    assert!(cfg.is_err());
+    assert_eq!(
+        cfg.unwrap_err().to_string(),
+        "[CONFIG][ERROR] Overwrite is false, found key: envInteger is duplicated"
+    );
+}
+
+// later document overwrites earlier one and other files with overwrite
+#[test]
+fn test_yaml_multi_document_overwrite_success() {
+    let cfg = Config::new()
+        .with_overwrite()
+        .with_yaml("./tests/data/yaml/test_multi_document_overwrite.yaml")
+        .build::<Overwrite>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.string, "overlay");
+    assert_eq!(cfg.integer, 123);
+}
+
+// `<<` merge keys are resolved, including chained merges and merge sequences
+#[test]
+fn test_yaml_merge_key_success() {
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Database {
+        adapter: String,
+        host: String,
+        port: u16,
+        pool: u32,
+        database: String,
+        timeout: Option<u32>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Cfg {
+        development: Database,
+        production: Database,
+    }
+
+    let cfg = Config::new()
+        .with_yaml("./tests/data/yaml/test_merge_key.yaml")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.development.adapter, "postgres");
+    assert_eq!(cfg.development.host, "localhost");
+    assert_eq!(cfg.development.port, 5432);
+    assert_eq!(cfg.development.pool, 5);
+    assert_eq!(cfg.development.database, "dev_db");
+    assert_eq!(cfg.development.timeout, None);
+    assert_eq!(cfg.production.adapter, "postgres");
+    assert_eq!(cfg.production.host, "db.prod");
+    assert_eq!(cfg.production.port, 5432);
+    assert_eq!(cfg.production.pool, 5);
+    assert_eq!(cfg.production.database, "prod_db");
+    assert_eq!(cfg.production.timeout, Some(30));
+}
+
+// no literal `<<` key is left in the merged map
+#[test]
+fn test_yaml_merge_key_removed_success() {
+    #[derive(Debug, Deserialize)]
+    struct Cfg {
+        development: std::collections::HashMap<String, Value>,
+    }
+
+    let cfg = Config::new()
+        .with_yaml("./tests/data/yaml/test_merge_key.yaml")
+        .build::<Cfg>();
+    dbg!(&cfg);
+    assert!(cfg.is_ok());
+    assert!(!cfg.unwrap().development.contains_key("<<"));
+}
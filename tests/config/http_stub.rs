@@ -0,0 +1,82 @@
+// local HTTP server stub for remote sources tests
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+// server answering each request with the response returned by its handler, in a background thread.
+pub struct Stub {
+    pub url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl Stub {
+    pub fn serve(handler: impl Fn(&str) -> String + Send + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        content_length = value.trim().parse().unwrap_or(0);
+                    }
+                    request.push_str(&line);
+                }
+                let mut body = vec![0; content_length];
+                let _ = reader.read_exact(&mut body);
+                request.push_str("\r\n");
+                request.push_str(&String::from_utf8_lossy(&body));
+
+                received.lock().unwrap().push(request.clone());
+                let _ = stream.write_all(handler(&request).as_bytes());
+            }
+        });
+        Self { url, requests }
+    }
+
+    // raw requests received so far, head and body.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+// url of a closed port, unreachable.
+pub fn unreachable_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    format!("http://{}", listener.local_addr().unwrap())
+}
+
+pub fn response(status: &str, headers: &[(&str, &str)], body: &str) -> String {
+    let mut ret = format!("HTTP/1.1 {}\r\n", status);
+    for (name, value) in headers {
+        ret.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    ret.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    ));
+    ret
+}
+
+// value of header in raw request, case insensitive.
+pub fn header<'a>(request: &'a str, name: &str) -> Option<&'a str> {
+    request.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
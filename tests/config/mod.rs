@@ -6,16 +6,77 @@ mod config_env_vars_test;
 
 mod config_env_test;
 
+mod config_env_parse_test;
+
 mod config_json_test;
 
+mod config_json5_test;
+
 mod config_toml_test;
 
+mod config_toml_datetime_test;
+
 mod config_yaml_test;
 
+mod config_yaml_document_test;
+
+mod config_ron_test;
+
 mod config_value_test;
 
 mod config_overwrite_test;
 
+mod config_layer_test;
+
+mod config_array_merge_test;
+
+mod config_unset_test;
+
 mod config_prefix_test;
 
+mod config_normalize_test;
+
+mod config_args_test;
+
+#[cfg(feature = "clap")]
+mod config_clap_test;
+
 mod config_mixes_test;
+
+mod config_reuse_test;
+
+mod config_build_at_test;
+
+mod config_lazy_test;
+
+mod config_validate_test;
+
+#[cfg(feature = "async")]
+mod config_async_test;
+
+#[cfg(feature = "http")]
+mod http_stub;
+
+#[cfg(feature = "http")]
+mod config_url_test;
+
+#[cfg(feature = "consul")]
+mod config_consul_test;
+
+#[cfg(feature = "etcd")]
+mod config_etcd_test;
+
+#[cfg(feature = "vault")]
+mod config_vault_test;
+
+#[cfg(feature = "sqlite")]
+mod config_sqlite_test;
+
+#[cfg(feature = "encryption")]
+mod config_encryption_test;
+
+#[cfg(feature = "sops")]
+mod config_sops_test;
+
+#[cfg(feature = "schema")]
+mod config_schema_test;